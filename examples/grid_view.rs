@@ -24,16 +24,16 @@ fn build_ui(app: &gtk::Application) {
 
         let animation = rlt::Animation::from_filename(hand_animation_path);
         animation.set_halign(gtk::Align::Center);
+        animation.set_natural_width(30);
+        animation.set_natural_height(30);
         animation.set_loop(true);
         animation.set_use_cache(false);
         animation.play();
         animation
     }
 
-    let vector: Vec<model::AnimationState> = (0..100_000)
-        .into_iter()
-        .map(|_| model::AnimationState::new())
-        .collect();
+    let vector: Vec<model::AnimationState> =
+        (0..100_000).map(|_| model::AnimationState::new()).collect();
 
     let model = gio::ListStore::new::<AnimationState>();
 
@@ -43,7 +43,7 @@ fn build_ui(app: &gtk::Application) {
     let factory = gtk::SignalListItemFactory::new();
     factory.connect_setup(|_factory, list_item| {
         let animation = create_animation();
-        let animation = fixed_size::FixedSizeBin::new(animation);
+        let list_item = list_item.downcast_ref::<gtk::ListItem>().unwrap();
         list_item.set_child(Some(&animation));
     });
 
@@ -105,64 +105,3 @@ mod model {
         }
     }
 }
-
-mod fixed_size {
-    use glib::Object;
-    use gtk::glib;
-
-    use super::*;
-
-    mod imp {
-        use gtk::glib::once_cell::sync::OnceCell;
-
-        use super::*;
-
-        #[derive(Default)]
-        pub struct FixedSizeBin(pub(super) OnceCell<rlt::Animation>);
-
-        #[glib::object_subclass]
-        impl ObjectSubclass for FixedSizeBin {
-            const NAME: &'static str = "MyGtkAppFixedSizeBin";
-            type ParentType = gtk::Widget;
-            type Type = super::FixedSizeBin;
-        }
-
-        impl ObjectImpl for FixedSizeBin {}
-        impl WidgetImpl for FixedSizeBin {
-            fn size_allocate(&self, width: i32, height: i32, baseline: i32) {
-                self.0
-                    .get()
-                    .unwrap()
-                    .allocate(width, height, baseline, None);
-            }
-
-            fn request_mode(&self) -> gtk::SizeRequestMode {
-                gtk::SizeRequestMode::ConstantSize
-            }
-
-            fn measure(&self, _: gtk::Orientation, _: i32) -> (i32, i32, i32, i32) {
-                (0, 30, -1, -1)
-            }
-        }
-
-        impl Drop for FixedSizeBin {
-            fn drop(&mut self) {
-                self.0.get().unwrap().unparent();
-            }
-        }
-    }
-
-    glib::wrapper! {
-        pub struct FixedSizeBin(ObjectSubclass<imp::FixedSizeBin>)
-            @extends gtk::Widget;
-    }
-
-    impl FixedSizeBin {
-        pub fn new(animation: rlt::Animation) -> Self {
-            let obj: Self = Object::new();
-            animation.set_parent(&obj);
-            obj.imp().0.set(animation).unwrap();
-            obj
-        }
-    }
-}
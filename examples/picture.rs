@@ -0,0 +1,32 @@
+use gtk::prelude::*;
+use gtk_rlottie as rlt; // I suggest to rename this package in dependencies
+
+const APP_ID: &str = "com.github.yuraiz.RltHello";
+
+fn main() {
+    let app = gtk::Application::builder().application_id(APP_ID).build();
+    app.connect_activate(build_ui);
+    app.run();
+}
+
+fn build_ui(app: &gtk::Application) {
+    // Paintable can be shown by any widget that draws paintables
+    let hand_animation_path = "examples/animations/AuthorizationStateWaitRegistration.tgs";
+
+    let paintable = rlt::AnimationPaintable::from_filename(hand_animation_path);
+    paintable.set_loop(true);
+    paintable.play();
+
+    let picture = gtk::Picture::for_paintable(&paintable);
+
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .width_request(200)
+        .height_request(200)
+        .child(&picture)
+        .build();
+
+    paintable.set_scale_factor(window.scale_factor());
+
+    window.present();
+}
@@ -0,0 +1,27 @@
+use gtk::prelude::*;
+use gtk_rlottie as rlt; // I suggest to rename this package in dependencies
+
+const APP_ID: &str = "com.github.yuraiz.RltHello";
+
+fn main() {
+    let app = gtk::Application::builder().application_id(APP_ID).build();
+    app.connect_activate(build_ui);
+    app.run();
+}
+
+fn build_ui(app: &gtk::Application) {
+    // gtk::Video shows media controls for the animation
+    let hand_animation_path = "examples/animations/AuthorizationStateWaitRegistration.tgs";
+
+    let stream = rlt::AnimationMediaStream::from_filename(hand_animation_path);
+
+    let video = gtk::Video::builder().media_stream(&stream).build();
+
+    let window = gtk::ApplicationWindow::builder()
+        .application(app)
+        .width_request(200)
+        .height_request(200)
+        .child(&video)
+        .build();
+    window.present();
+}
@@ -0,0 +1,1147 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::gio;
+use gtk::glib;
+use gtk::graphene;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use crate::paintable;
+use crate::AnimationClock;
+use crate::AnimationPaintable;
+use crate::ContentFit;
+use crate::Error;
+use crate::Interaction;
+use crate::Marker;
+use crate::PlaybackMode;
+use crate::Recolor;
+use crate::ReducedMotionPolicy;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, Debug)]
+    pub struct Animation {
+        pub(super) paintable: AnimationPaintable,
+        pub(super) reduced_motion_policy: RefCell<ReducedMotionPolicy>,
+        pub(super) settings_handler: RefCell<Option<(gtk::Settings, glib::SignalHandlerId)>>,
+        /// Whether animations are disabled and the policy applies.
+        pub(super) motion_reduced: Cell<bool>,
+        /// Whether to play once animations are enabled again.
+        pub(super) wants_playing: Cell<bool>,
+        pub(super) autoplay: Cell<bool>,
+        pub(super) interaction: Cell<Interaction>,
+        pub(super) content_fit: Cell<ContentFit>,
+        /// Requested size, -1 when unset.
+        pub(super) natural_width: Cell<i32>,
+        pub(super) natural_height: Cell<i32>,
+        pub(super) recolor: Cell<Recolor>,
+        /// Color the animation is tinted with, its alpha is applied when drawing.
+        pub(super) tint: Cell<Option<gdk::RGBA>>,
+        pub(super) toplevel_handler: RefCell<Option<(gdk::Toplevel, glib::SignalHandlerId)>>,
+        /// Whether the widget is mapped in a toplevel that is on screen.
+        pub(super) shown: Cell<bool>,
+        /// Whether to play once the widget is shown again.
+        pub(super) paused_while_hidden: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for Animation {
+        const NAME: &'static str = "LottieAnimation";
+        type Type = super::Animation;
+        type ParentType = gtk::Widget;
+
+        fn class_init(klass: &mut Self::Class) {
+            // SAFETY: class struct of the widget starts with the one of GtkWidget
+            let widget_class =
+                unsafe { &mut *(klass as *mut Self::Class as *mut gtk::ffi::GtkWidgetClass) };
+
+            // css_changed isn't in WidgetImpl, GtkCssStyleChange is private
+            widget_class.css_changed = Some(css_changed);
+        }
+    }
+
+    unsafe extern "C" fn css_changed(
+        widget: *mut gtk::ffi::GtkWidget,
+        change: *mut gtk::ffi::GtkCssStyleChange,
+    ) {
+        let parent_class =
+            Animation::type_data().as_ref().parent_class() as *const gtk::ffi::GtkWidgetClass;
+
+        if let Some(parent_css_changed) = (*parent_class).css_changed {
+            parent_css_changed(widget, change);
+        }
+
+        let widget: glib::translate::Borrowed<gtk::Widget> =
+            glib::translate::from_glib_borrow(widget);
+
+        if let Some(obj) = widget.downcast_ref::<super::Animation>() {
+            obj.imp().update_tint();
+        }
+    }
+
+    impl ObjectImpl for Animation {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+
+            self.natural_width.set(-1);
+            self.natural_height.set(-1);
+
+            self.paintable.set_owner(&*obj);
+            self.paintable.set_scale_factor(obj.scale_factor());
+
+            obj.connect_scale_factor_notify(|obj| {
+                obj.imp().paintable.set_scale_factor(obj.scale_factor());
+            });
+
+            self.paintable.connect_invalidate_contents(clone!(
+                #[weak]
+                obj,
+                move |_| obj.queue_draw()
+            ));
+
+            self.paintable.connect_invalidate_size(clone!(
+                #[weak]
+                obj,
+                move |_| obj.queue_resize()
+            ));
+
+            self.paintable.connect_notify_local(
+                None,
+                clone!(
+                    #[weak]
+                    obj,
+                    move |_, pspec| {
+                        if obj.find_property(pspec.name()).is_some() {
+                            obj.notify(pspec.name());
+                        }
+                    }
+                ),
+            );
+
+            for signal in Self::signals() {
+                let name = signal.name();
+
+                self.paintable.connect_local(
+                    name,
+                    false,
+                    clone!(
+                        #[weak]
+                        obj,
+                        #[upgrade_or]
+                        None,
+                        move |args| obj.emit_by_name_with_values(name, &args[1..])
+                    ),
+                );
+            }
+
+            let motion = gtk::EventControllerMotion::new();
+
+            motion.connect_enter(clone!(
+                #[weak]
+                obj,
+                move |_, _, _| {
+                    if obj.interaction() == Interaction::PlayOnHover {
+                        obj.play();
+                    }
+                }
+            ));
+
+            motion.connect_leave(clone!(
+                #[weak]
+                obj,
+                move |_| {
+                    if obj.interaction() == Interaction::PlayOnHover {
+                        obj.pause();
+                    }
+                }
+            ));
+
+            obj.add_controller(motion);
+
+            let click = gtk::GestureClick::new();
+
+            click.connect_released(clone!(
+                #[weak]
+                obj,
+                move |gesture, _, _, _| {
+                    match obj.interaction() {
+                        Interaction::None | Interaction::PlayOnHover => return,
+                        Interaction::PlayOnClick => obj.play(),
+                        Interaction::ToggleOnClick => {
+                            if obj.is_playing() {
+                                obj.pause();
+                            } else {
+                                obj.play();
+                            }
+                        }
+                        Interaction::RestartOnClick => {
+                            obj.imp().paintable.rewind();
+                            obj.play();
+                        }
+                    }
+
+                    gesture.set_state(gtk::EventSequenceState::Claimed);
+                }
+            ));
+
+            obj.add_controller(click);
+
+            self.paintable.connect_loaded(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| {
+                    if imp.motion_reduced.get() {
+                        imp.show_still_frame();
+                    }
+                }
+            ));
+        }
+
+        fn dispose(&self) {
+            self.disconnect_settings();
+            self.disconnect_toplevel();
+        }
+
+        fn signals() -> &'static [glib::subclass::Signal] {
+            static SIGNALS: OnceLock<Vec<glib::subclass::Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(paintable::shared_signals)
+        }
+
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: OnceLock<Vec<glib::ParamSpec>> = OnceLock::new();
+            PROPERTIES.get_or_init(|| {
+                let mut properties = paintable::shared_properties();
+                properties.push(
+                    glib::ParamSpecObject::builder::<AnimationPaintable>("paintable")
+                        .read_only()
+                        .build(),
+                );
+                properties.push(
+                    glib::ParamSpecBoolean::builder("autoplay")
+                        .explicit_notify()
+                        .build(),
+                );
+                properties.push(
+                    glib::ParamSpecEnum::builder::<ContentFit>("content-fit")
+                        .explicit_notify()
+                        .build(),
+                );
+                properties.push(
+                    glib::ParamSpecInt::builder("natural-width")
+                        .minimum(-1)
+                        .default_value(-1)
+                        .explicit_notify()
+                        .build(),
+                );
+                properties.push(
+                    glib::ParamSpecInt::builder("natural-height")
+                        .minimum(-1)
+                        .default_value(-1)
+                        .explicit_notify()
+                        .build(),
+                );
+                properties.push(
+                    glib::ParamSpecEnum::builder::<Interaction>("interaction")
+                        .explicit_notify()
+                        .build(),
+                );
+                properties.push(
+                    glib::ParamSpecEnum::builder::<Recolor>("recolor")
+                        .explicit_notify()
+                        .build(),
+                );
+                properties.push(
+                    glib::ParamSpecBoxed::builder::<ReducedMotionPolicy>("reduced-motion-policy")
+                        .explicit_notify()
+                        .build(),
+                );
+                properties
+            })
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "autoplay" => self.autoplay.get().to_value(),
+                "content-fit" => self.content_fit.get().to_value(),
+                "interaction" => self.interaction.get().to_value(),
+                "natural-height" => self.natural_height.get().to_value(),
+                "natural-width" => self.natural_width.get().to_value(),
+                "paintable" => self.paintable.to_value(),
+                "recolor" => self.recolor.get().to_value(),
+                "reduced-motion-policy" => self.reduced_motion_policy.borrow().to_value(),
+                name => self.paintable.property_value(name),
+            }
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            // the app decided while hidden, play or not once shown
+            if pspec.name() == "playing" && !self.shown.get() {
+                self.paused_while_hidden.set(value.get().unwrap());
+                return;
+            }
+
+            match pspec.name() {
+                "autoplay" => {
+                    let autoplay = value.get().unwrap();
+                    if self.autoplay.replace(autoplay) != autoplay {
+                        if autoplay && self.shown.get() {
+                            self.obj().play();
+                        }
+
+                        self.obj().notify("autoplay");
+                    }
+                }
+                "content-fit" => {
+                    let content_fit = value.get().unwrap();
+                    if self.content_fit.replace(content_fit) != content_fit {
+                        let obj = self.obj();
+                        obj.queue_resize();
+                        obj.notify("content-fit");
+                    }
+                }
+                "interaction" => {
+                    let interaction = value.get().unwrap();
+                    if self.interaction.replace(interaction) != interaction {
+                        self.obj().notify("interaction");
+                    }
+                }
+                "natural-height" => {
+                    let height = value.get().unwrap();
+                    if self.natural_height.replace(height) != height {
+                        let obj = self.obj();
+                        obj.queue_resize();
+                        obj.notify("natural-height");
+                    }
+                }
+                "natural-width" => {
+                    let width = value.get().unwrap();
+                    if self.natural_width.replace(width) != width {
+                        let obj = self.obj();
+                        obj.queue_resize();
+                        obj.notify("natural-width");
+                    }
+                }
+                "recolor" => {
+                    let recolor = value.get().unwrap();
+                    if self.recolor.replace(recolor) != recolor {
+                        self.update_tint();
+                        self.obj().notify("recolor");
+                    }
+                }
+                "reduced-motion-policy" => {
+                    let policy = value.get().unwrap();
+                    if policy != *self.reduced_motion_policy.borrow() {
+                        self.reduced_motion_policy.replace(policy);
+                        self.update_reduced_motion();
+                        self.obj().notify("reduced-motion-policy");
+                    }
+                }
+                // don't play until animations are enabled
+                "playing" if self.motion_reduced.get() => {
+                    self.wants_playing.set(value.get().unwrap());
+                }
+                name => self.paintable.set_property_from_value(name, value),
+            }
+        }
+    }
+
+    impl WidgetImpl for Animation {
+        fn root(&self) {
+            self.parent_root();
+
+            let settings = self.obj().settings();
+
+            let handler = settings.connect_gtk_enable_animations_notify(clone!(
+                #[weak(rename_to = imp)]
+                self,
+                move |_| imp.update_reduced_motion()
+            ));
+
+            self.settings_handler.replace(Some((settings, handler)));
+            self.update_reduced_motion();
+        }
+
+        fn unroot(&self) {
+            self.disconnect_settings();
+            self.parent_unroot();
+        }
+
+        fn map(&self) {
+            self.parent_map();
+
+            let toplevel = self
+                .obj()
+                .native()
+                .and_then(|native| native.surface())
+                .and_downcast::<gdk::Toplevel>();
+
+            if let Some(toplevel) = toplevel {
+                let handler = toplevel.connect_state_notify(clone!(
+                    #[weak(rename_to = imp)]
+                    self,
+                    move |_| imp.update_shown()
+                ));
+
+                self.toplevel_handler.replace(Some((toplevel, handler)));
+            }
+
+            self.update_shown();
+        }
+
+        fn unmap(&self) {
+            self.disconnect_toplevel();
+            self.parent_unmap();
+            self.update_shown();
+        }
+
+        fn snapshot(&self, snapshot: &gtk::Snapshot) {
+            let widget = self.obj();
+
+            let width = widget.width() as f64;
+            let height = widget.height() as f64;
+
+            let aspect_ratio = self.paintable.intrinsic_aspect_ratio();
+
+            if aspect_ratio == 0.0 || width <= 0.0 || height <= 0.0 {
+                return;
+            }
+
+            // size that fits in the given area, keeping the aspect ratio
+            let contain = |width: f64, height: f64| {
+                if aspect_ratio < width / height {
+                    (height * aspect_ratio, height)
+                } else {
+                    (width, width / aspect_ratio)
+                }
+            };
+
+            let content_fit = self.content_fit.get();
+
+            let (content_width, content_height) = match content_fit {
+                ContentFit::Fill => (width, height),
+                ContentFit::Contain => contain(width, height),
+                ContentFit::Cover => {
+                    if aspect_ratio < width / height {
+                        (width, width / aspect_ratio)
+                    } else {
+                        (height * aspect_ratio, height)
+                    }
+                }
+                ContentFit::ScaleDown => {
+                    let (natural_width, natural_height) = self.natural_size();
+
+                    contain(
+                        width.min(natural_width as f64),
+                        height.min(natural_height as f64),
+                    )
+                }
+            };
+
+            if content_fit == ContentFit::Cover {
+                snapshot.push_clip(&graphene::Rect::new(0.0, 0.0, width as f32, height as f32));
+            }
+
+            let alpha = self.tint.get().map_or(1.0, |tint| tint.alpha());
+
+            if alpha < 1.0 {
+                snapshot.push_opacity(alpha as f64);
+            }
+
+            snapshot.save();
+            snapshot.translate(&graphene::Point::new(
+                ((width - content_width) / 2.0) as f32,
+                ((height - content_height) / 2.0) as f32,
+            ));
+
+            self.paintable
+                .snapshot(snapshot, content_width, content_height);
+
+            snapshot.restore();
+
+            if alpha < 1.0 {
+                snapshot.pop();
+            }
+
+            if content_fit == ContentFit::Cover {
+                snapshot.pop();
+            }
+        }
+
+        fn request_mode(&self) -> gtk::SizeRequestMode {
+            if self.has_constant_size() {
+                gtk::SizeRequestMode::ConstantSize
+            } else {
+                gtk::SizeRequestMode::HeightForWidth
+            }
+        }
+
+        fn measure(&self, orientation: gtk::Orientation, for_size: i32) -> (i32, i32, i32, i32) {
+            let (width, height) = self.natural_size();
+
+            let natural_size = match orientation {
+                gtk::Orientation::Horizontal => width,
+                gtk::Orientation::Vertical => height,
+                _ => unimplemented!(),
+            };
+
+            let aspect_ratio = self.paintable.intrinsic_aspect_ratio();
+
+            if for_size < 0 || aspect_ratio == 0.0 || self.has_constant_size() {
+                return (0, natural_size, -1, -1);
+            }
+
+            let size = match orientation {
+                // width for the given height
+                gtk::Orientation::Horizontal => (for_size as f64 * aspect_ratio).ceil() as i32,
+                // height for the given width
+                gtk::Orientation::Vertical => (for_size as f64 / aspect_ratio).ceil() as i32,
+                _ => unimplemented!(),
+            };
+
+            let size = if self.content_fit.get() == ContentFit::ScaleDown {
+                size.min(natural_size)
+            } else {
+                size
+            };
+
+            (0, size, -1, -1)
+        }
+    }
+
+    impl Animation {
+        /// Returns the requested size, or the default size of the animation.
+        ///
+        /// When only one side is set, the other one keeps the aspect ratio.
+        fn natural_size(&self) -> (i32, i32) {
+            let aspect_ratio = self.paintable.intrinsic_aspect_ratio();
+
+            match (self.natural_width.get(), self.natural_height.get()) {
+                (-1, -1) => (
+                    self.paintable.intrinsic_width(),
+                    self.paintable.intrinsic_height(),
+                ),
+                (width, -1) if aspect_ratio != 0.0 => {
+                    (width, (width as f64 / aspect_ratio).ceil() as i32)
+                }
+                (-1, height) if aspect_ratio != 0.0 => {
+                    ((height as f64 * aspect_ratio).ceil() as i32, height)
+                }
+                (-1, height) => (height, height),
+                (width, -1) => (width, width),
+                size => size,
+            }
+        }
+
+        /// Whether the size doesn't depend on the allocation,
+        /// because it is set explicitly or the aspect ratio isn't kept.
+        fn has_constant_size(&self) -> bool {
+            self.natural_width.get() != -1
+                || self.natural_height.get() != -1
+                || matches!(self.content_fit.get(), ContentFit::Fill | ContentFit::Cover)
+        }
+
+        /// Tints the animation with the css color chosen by the recolor mode.
+        fn update_tint(&self) {
+            let tint = self.tint_color();
+
+            if self.tint.replace(tint) != tint {
+                self.paintable.set_tint(tint.as_ref());
+                self.obj().queue_draw();
+            }
+        }
+
+        // style context is the only way to look up named colors
+        #[allow(deprecated)]
+        fn tint_color(&self) -> Option<gdk::RGBA> {
+            let recolor = self.recolor.get();
+
+            if recolor == Recolor::None {
+                return None;
+            }
+
+            let obj = self.obj();
+            let style_context = obj.style_context();
+
+            let named_color = recolor
+                .color_name()
+                .and_then(|name| style_context.lookup_color(name));
+
+            #[cfg(feature = "v4_12")]
+            let color = obj.color();
+            #[cfg(not(feature = "v4_12"))]
+            let color = style_context.color();
+
+            Some(named_color.unwrap_or(color))
+        }
+
+        fn disconnect_toplevel(&self) {
+            if let Some((toplevel, handler)) = self.toplevel_handler.take() {
+                toplevel.disconnect(handler);
+            }
+        }
+
+        /// Pauses while nobody can see the widget, and resumes after.
+        fn update_shown(&self) {
+            let obj = self.obj();
+
+            let toplevel_hidden = self
+                .toplevel_handler
+                .borrow()
+                .as_ref()
+                .is_some_and(|(toplevel, _)| is_hidden(toplevel));
+
+            let shown = obj.is_mapped() && !toplevel_hidden;
+
+            if self.shown.replace(shown) == shown {
+                return;
+            }
+
+            if shown {
+                if self.paused_while_hidden.take() || self.autoplay.get() {
+                    obj.play();
+                }
+            } else if self.paintable.is_playing() {
+                self.paused_while_hidden.set(true);
+                self.paintable.pause();
+            }
+        }
+
+        fn disconnect_settings(&self) {
+            if let Some((settings, handler)) = self.settings_handler.take() {
+                settings.disconnect(handler);
+            }
+        }
+
+        /// Stops or resumes playback when animations are disabled or enabled.
+        fn update_reduced_motion(&self) {
+            let animations_enabled = self
+                .settings_handler
+                .borrow()
+                .as_ref()
+                .is_none_or(|(settings, _)| settings.is_gtk_enable_animations());
+
+            let reduced = !animations_enabled
+                && *self.reduced_motion_policy.borrow() != ReducedMotionPolicy::Ignore;
+
+            if reduced {
+                if !self.motion_reduced.replace(true) {
+                    self.wants_playing.set(self.paintable.is_playing());
+                }
+
+                self.paintable.pause();
+                self.show_still_frame();
+            } else if self.motion_reduced.replace(false) && self.wants_playing.get() {
+                if self.shown.get() {
+                    self.paintable.play();
+                } else {
+                    self.paused_while_hidden.set(true);
+                }
+            }
+        }
+
+        /// Shows the frame chosen by the reduced motion policy.
+        fn show_still_frame(&self) {
+            match &*self.reduced_motion_policy.borrow() {
+                ReducedMotionPolicy::Ignore => (),
+                ReducedMotionPolicy::ShowFirstFrame => self.paintable.set_progress(0.0),
+                ReducedMotionPolicy::ShowLastFrame => self.paintable.set_progress(1.0),
+                ReducedMotionPolicy::ShowMarker(name) => {
+                    if !self.paintable.seek_marker(name) {
+                        self.paintable.set_progress(0.0);
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn is_hidden(toplevel: &gdk::Toplevel) -> bool {
+    let state = toplevel.state();
+
+    #[cfg(feature = "v4_12")]
+    if state.contains(gdk::ToplevelState::SUSPENDED) {
+        return true;
+    }
+
+    state.contains(gdk::ToplevelState::MINIMIZED)
+}
+
+glib::wrapper! {
+    /// Widget that displays vector lottie animation
+    ///
+    /// It draws an [`AnimationPaintable`], all properties
+    /// of the paintable are available on the widget too.
+    pub struct Animation(ObjectSubclass<imp::Animation>)
+        @extends gtk::Widget;
+}
+
+impl Animation {
+    /// Returns the paintable that renders the animation.
+    pub fn paintable(&self) -> AnimationPaintable {
+        self.imp().paintable.clone()
+    }
+
+    /// Shows the frame, same as [`Animation::set_frame`].
+    pub fn request_draw(&self, frame_num: usize) {
+        self.imp().paintable.request_draw(frame_num);
+    }
+
+    /// Returns the current frame.
+    ///
+    /// See [`AnimationPaintable::frame`].
+    pub fn frame(&self) -> usize {
+        self.imp().paintable.frame()
+    }
+
+    /// Moves to the frame, playback continues from it.
+    ///
+    /// See [`AnimationPaintable::set_frame`].
+    pub fn set_frame(&self, frame_num: usize) {
+        self.imp().paintable.set_frame(frame_num);
+    }
+
+    /// Returns the frame that is on screen.
+    pub fn displayed_frame(&self) -> Option<usize> {
+        self.imp().paintable.displayed_frame()
+    }
+
+    /// Moves to the next frame, it stops on the last one.
+    pub fn step_forward(&self) {
+        self.imp().paintable.step_forward();
+    }
+
+    /// Moves to the previous frame, it stops on the first one.
+    pub fn step_backward(&self) {
+        self.imp().paintable.step_backward();
+    }
+
+    pub fn open(&self, file: gio::File) {
+        self.imp().paintable.open(file);
+    }
+
+    /// Opens json or tgs data.
+    ///
+    /// See [`AnimationPaintable::open_bytes`] for `cache_key`.
+    pub fn open_bytes(&self, data: &glib::Bytes, cache_key: &str) {
+        self.imp().paintable.open_bytes(data, cache_key);
+    }
+
+    /// Reads the stream to the end and opens its data as json or tgs.
+    ///
+    /// See [`AnimationPaintable::open_bytes`] for `cache_key`.
+    pub async fn load_from_stream(
+        &self,
+        stream: &impl IsA<gio::InputStream>,
+        cache_key: &str,
+    ) -> Result<(), Error> {
+        self.imp()
+            .paintable
+            .load_from_stream(stream, cache_key)
+            .await
+    }
+
+    /// Creates animation from json of tgs files.
+    pub fn from_file(file: &impl IsA<gio::File>) -> Self {
+        let obj: Self = glib::Object::new();
+        obj.open(file.to_owned().upcast());
+        obj
+    }
+
+    /// Creates animation from json of tgs files from the given filename.
+    pub fn from_filename(path: &str) -> Self {
+        let file = gio::File::for_path(path);
+        Self::from_file(&file)
+    }
+
+    /// Creates animation from json or tgs file in the resource bundle.
+    pub fn from_resource(path: &str) -> Self {
+        let file = gio::File::for_uri(&format!("resource://{path}"));
+        Self::from_file(&file)
+    }
+
+    /// Creates animation from json or tgs data.
+    ///
+    /// See [`AnimationPaintable::open_bytes`] for `cache_key`.
+    pub fn from_bytes(data: &glib::Bytes, cache_key: &str) -> Self {
+        let obj: Self = glib::Object::new();
+        obj.open_bytes(data, cache_key);
+        obj
+    }
+
+    /// Returns the error that occurred while loading the animation, if any.
+    pub fn error(&self) -> Option<Error> {
+        self.property("error")
+    }
+
+    /// Connects to the signal emitted when the animation could not be loaded.
+    pub fn connect_load_failed<F: Fn(&Self, &Error) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "load-failed",
+            false,
+            glib::closure_local!(move |obj: &Self, error: &Error| f(obj, error)),
+        )
+    }
+
+    /// Returns whether the animation is loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.property("loaded")
+    }
+
+    /// Returns how many frames the animation has.
+    ///
+    /// It's zero until the animation is loaded.
+    pub fn total_frames(&self) -> usize {
+        self.imp().paintable.total_frames()
+    }
+
+    /// Returns the duration of the animation.
+    ///
+    /// It's zero until the animation is loaded.
+    pub fn duration(&self) -> Duration {
+        self.imp().paintable.duration()
+    }
+
+    /// Returns frames per second of the animation.
+    ///
+    /// It's zero until the animation is loaded.
+    pub fn framerate(&self) -> f64 {
+        self.property("framerate")
+    }
+
+    /// Connects to the signal emitted when the animation is loaded.
+    pub fn connect_loaded<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "loaded",
+            false,
+            glib::closure_local!(move |obj: &Self| f(obj)),
+        )
+    }
+
+    /// Connects to the signal emitted when a new frame is shown.
+    pub fn connect_frame_changed<F: Fn(&Self, u32) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "frame-changed",
+            false,
+            glib::closure_local!(move |obj: &Self, frame: u32| f(obj, frame)),
+        )
+    }
+
+    /// Connects to the signal emitted when looping playback starts over.
+    ///
+    /// The callback gets how many times the animation looped.
+    pub fn connect_looped<F: Fn(&Self, u32) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "looped",
+            false,
+            glib::closure_local!(move |obj: &Self, count: u32| f(obj, count)),
+        )
+    }
+
+    /// Connects to the signal emitted when playback stops on the last frame.
+    pub fn connect_ended<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "ended",
+            false,
+            glib::closure_local!(move |obj: &Self| f(obj)),
+        )
+    }
+
+    /// Return whether the animation is currently using cache.
+    pub fn use_cache(&self, value: bool) {
+        self.set_property("use-cache", value);
+    }
+
+    /// Set to use the cache or not.
+    ///
+    /// By default animation have the cache
+    /// it uses ram to reduse cpu usage
+    ///
+    /// and you can disable it when animation
+    /// plays once and don't need a cache
+    pub fn set_use_cache(&self, value: bool) {
+        self.set_property("use-cache", value);
+    }
+
+    /// Reversed frame order.
+    pub fn is_reversed(&self) -> bool {
+        self.property("reversed")
+    }
+
+    /// Sets reversed or default frame order.
+    pub fn set_reversed(&self, value: bool) {
+        self.set_property("reversed", value);
+    }
+
+    /// Returns the clock the animation follows.
+    pub fn clock(&self) -> Option<AnimationClock> {
+        self.property("clock")
+    }
+
+    /// Makes the animation follow the shared clock, or its own one with `None`.
+    ///
+    /// See [`AnimationPaintable::set_clock`].
+    pub fn set_clock(&self, clock: Option<&AnimationClock>) {
+        self.set_property("clock", clock);
+    }
+
+    /// Returns whether the animation plays when the widget is shown.
+    pub fn is_autoplay(&self) -> bool {
+        self.property("autoplay")
+    }
+
+    /// Sets whether the animation plays when the widget is shown.
+    ///
+    /// Playback pauses when the widget is unmapped, or its window is
+    /// minimized or suspended, whether autoplay is set or not.
+    pub fn set_autoplay(&self, autoplay: bool) {
+        self.set_property("autoplay", autoplay);
+    }
+
+    /// Returns how the animation is fitted in the widget.
+    pub fn content_fit(&self) -> ContentFit {
+        self.property("content-fit")
+    }
+
+    /// Sets how the animation is fitted in the widget.
+    pub fn set_content_fit(&self, content_fit: ContentFit) {
+        self.set_property("content-fit", content_fit);
+    }
+
+    /// Returns the requested width, or -1 to use the default width of the animation.
+    pub fn natural_width(&self) -> i32 {
+        self.property("natural-width")
+    }
+
+    /// Sets the requested width in pixels, like [`gtk::Image::set_pixel_size`].
+    ///
+    /// Pass -1 to use the default width, or to keep the aspect ratio
+    /// when only the natural height is set.
+    pub fn set_natural_width(&self, width: i32) {
+        self.set_property("natural-width", width);
+    }
+
+    /// Returns the requested height, or -1 to use the default height of the animation.
+    pub fn natural_height(&self) -> i32 {
+        self.property("natural-height")
+    }
+
+    /// Sets the requested height in pixels, like [`gtk::Image::set_pixel_size`].
+    ///
+    /// Pass -1 to use the default height, or to keep the aspect ratio
+    /// when only the natural width is set.
+    pub fn set_natural_height(&self, height: i32) {
+        self.set_property("natural-height", height);
+    }
+
+    /// Returns which css color tints the animation.
+    pub fn recolor(&self) -> Recolor {
+        self.property("recolor")
+    }
+
+    /// Sets which css color tints the animation, it follows style changes.
+    ///
+    /// Alpha of the color is applied to the whole animation.
+    pub fn set_recolor(&self, recolor: Recolor) {
+        self.set_property("recolor", recolor);
+    }
+
+    /// Returns how the widget reacts to the pointer.
+    pub fn interaction(&self) -> Interaction {
+        self.property("interaction")
+    }
+
+    /// Sets how the widget reacts to the pointer.
+    ///
+    /// Playback still follows the loop and the segment, so
+    /// without looping a hover or a click plays the animation once.
+    pub fn set_interaction(&self, interaction: Interaction) {
+        self.set_property("interaction", interaction);
+    }
+
+    /// Returns what is shown when animations are disabled.
+    pub fn reduced_motion_policy(&self) -> ReducedMotionPolicy {
+        self.property("reduced-motion-policy")
+    }
+
+    /// Sets what is shown when animations are disabled in [`gtk::Settings`].
+    ///
+    /// Playback waits until animations are enabled again,
+    /// unless the policy is [`ReducedMotionPolicy::Ignore`].
+    pub fn set_reduced_motion_policy(&self, policy: ReducedMotionPolicy) {
+        self.set_property("reduced-motion-policy", policy);
+    }
+
+    /// Returns the order frames are played in.
+    pub fn playback_mode(&self) -> PlaybackMode {
+        self.property("playback-mode")
+    }
+
+    /// Sets the order frames are played in.
+    ///
+    /// See [`AnimationPaintable::set_playback_mode`].
+    pub fn set_playback_mode(&self, playback_mode: PlaybackMode) {
+        self.set_property("playback-mode", playback_mode);
+    }
+
+    /// Returns the playback speed.
+    pub fn speed(&self) -> f64 {
+        self.property("speed")
+    }
+
+    /// Sets the playback speed, `1.0` is the normal speed.
+    ///
+    /// See [`AnimationPaintable::set_speed`].
+    pub fn set_speed(&self, speed: f64) {
+        self.set_property("speed", speed);
+    }
+
+    /// Limits playback to frames from `start` up to, but not including, `end`.
+    ///
+    /// See [`AnimationPaintable::set_segment`].
+    pub fn set_segment(&self, start: usize, end: usize) {
+        self.imp().paintable.set_segment(start, end);
+    }
+
+    /// Plays the segment from its start.
+    pub fn play_segment(&self, start: usize, end: usize, loop_: bool) {
+        self.imp().paintable.play_segment(start, end, loop_);
+    }
+
+    /// Returns the segment playback is limited to.
+    pub fn segment(&self) -> Option<(usize, usize)> {
+        self.imp().paintable.segment()
+    }
+
+    /// Plays the whole animation again.
+    pub fn clear_segment(&self) {
+        self.imp().paintable.clear_segment();
+    }
+
+    /// Sets the fill color of the layers matching the keypath.
+    ///
+    /// See [`AnimationPaintable::set_fill_color`].
+    pub fn set_fill_color(&self, keypath: &str, color: &gdk::RGBA) {
+        self.imp().paintable.set_fill_color(keypath, color);
+    }
+
+    /// Sets the stroke color of the layers matching the keypath.
+    pub fn set_stroke_color(&self, keypath: &str, color: &gdk::RGBA) {
+        self.imp().paintable.set_stroke_color(keypath, color);
+    }
+
+    /// Sets the opacity of fills and strokes matching the keypath, from 0 to 1.
+    pub fn set_opacity(&self, keypath: &str, opacity: f64) {
+        self.imp().paintable.set_opacity(keypath, opacity);
+    }
+
+    /// Sets the stroke width of the layers matching the keypath.
+    pub fn set_stroke_width(&self, keypath: &str, width: f64) {
+        self.imp().paintable.set_stroke_width(keypath, width);
+    }
+
+    /// Removes overrides set for the keypath.
+    pub fn clear_override(&self, keypath: &str) {
+        self.imp().paintable.clear_override(keypath);
+    }
+
+    /// Returns markers of the animation.
+    ///
+    /// It's empty until the animation is loaded.
+    pub fn markers(&self) -> Vec<Marker> {
+        self.imp().paintable.markers()
+    }
+
+    /// Plays the part of the animation under the marker.
+    ///
+    /// See [`AnimationPaintable::play_marker`].
+    pub fn play_marker(&self, name: &str) -> bool {
+        self.imp().paintable.play_marker(name)
+    }
+
+    /// Moves to the first frame of the marker, the segment is cleared.
+    ///
+    /// See [`AnimationPaintable::seek_marker`].
+    pub fn seek_marker(&self, name: &str) -> bool {
+        self.imp().paintable.seek_marker(name)
+    }
+
+    /// Moves to the frame shown at the time from the start of the animation.
+    ///
+    /// See [`AnimationPaintable::seek`].
+    pub fn seek(&self, time: Duration) {
+        self.imp().paintable.seek(time);
+    }
+
+    /// Returns the time of the current frame from the start of the animation.
+    pub fn position(&self) -> Duration {
+        self.imp().paintable.position()
+    }
+
+    /// Returns current progress.
+    pub fn progress(&self) -> f64 {
+        self.property("progress")
+    }
+
+    /// Sets current progress.
+    pub fn set_progress(&self, value: f64) {
+        self.set_property("progress", value);
+    }
+
+    // Media functions
+
+    /// Return whether the animation is currently playing.
+    pub fn is_playing(&self) -> bool {
+        self.imp().paintable.is_playing()
+    }
+
+    /// Play the animation.
+    pub fn play(&self) {
+        self.set_property("playing", true);
+    }
+
+    /// Pause the animation.
+    pub fn pause(&self) {
+        self.set_property("playing", false);
+    }
+
+    /// Returns whether the animation is set to loop.
+    pub fn is_loop(&self) -> bool {
+        self.property("loop")
+    }
+
+    /// Sets whether the animation should loop.
+    pub fn set_loop(&self, loop_: bool) {
+        self.set_property("loop", loop_);
+    }
+
+    /// Returns how many times the animation repeats.
+    pub fn loop_count(&self) -> i32 {
+        self.property("loop-count")
+    }
+
+    /// Sets how many times the animation repeats after playing once.
+    ///
+    /// `0` plays it once and `-1` loops forever.
+    pub fn set_loop_count(&self, loop_count: i32) {
+        self.set_property("loop-count", loop_count.max(-1));
+    }
+
+    /// Returns how many times the animation started over since playback started.
+    pub fn iteration(&self) -> u32 {
+        self.property("iteration")
+    }
+}
@@ -0,0 +1,137 @@
+use std::cell::Cell;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, Debug)]
+    pub struct AnimationClock {
+        /// Monotonic time when the clock was at zero, used while playing.
+        pub(super) start_time: Cell<i64>,
+        /// Time of the paused clock.
+        pub(super) paused_time: Cell<i64>,
+        pub(super) playing: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AnimationClock {
+        const NAME: &'static str = "LottieAnimationClock";
+        type Type = super::AnimationClock;
+    }
+
+    impl ObjectImpl for AnimationClock {
+        fn signals() -> &'static [glib::subclass::Signal] {
+            static SIGNALS: OnceLock<Vec<glib::subclass::Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(|| vec![glib::subclass::Signal::builder("seeked").build()])
+        }
+
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: OnceLock<Vec<glib::ParamSpec>> = OnceLock::new();
+            PROPERTIES.get_or_init(|| {
+                vec![glib::ParamSpecBoolean::builder("playing")
+                    .explicit_notify()
+                    .build()]
+            })
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "playing" => self.playing.get().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "playing" => {
+                    let playing = value.get().unwrap();
+                    let time = self.time();
+
+                    if self.playing.replace(playing) != playing {
+                        self.set_time(time);
+                        self.obj().notify("playing");
+                    }
+                }
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl AnimationClock {
+        /// Returns the time in microseconds.
+        pub(crate) fn time(&self) -> i64 {
+            if self.playing.get() {
+                glib::monotonic_time() - self.start_time.get()
+            } else {
+                self.paused_time.get()
+            }
+        }
+
+        pub(super) fn set_time(&self, time: i64) {
+            self.start_time.set(glib::monotonic_time() - time);
+            self.paused_time.set(time);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Timeline shared by several animations
+    ///
+    /// Animations attached with [`crate::AnimationPaintable::set_clock`]
+    /// compute their frames from the time of the clock, so they play in lockstep.
+    /// Play, pause and seek the clock to drive all of them.
+    pub struct AnimationClock(ObjectSubclass<imp::AnimationClock>);
+}
+
+impl Default for AnimationClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnimationClock {
+    /// Creates paused clock at zero.
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    /// Returns the time of the clock.
+    pub fn time(&self) -> Duration {
+        Duration::from_micros(self.imp().time().max(0) as u64)
+    }
+
+    /// Moves the clock and all its animations to the time.
+    pub fn seek(&self, time: Duration) {
+        self.imp().set_time(time.as_micros() as i64);
+        self.emit_by_name::<()>("seeked", &[]);
+    }
+
+    /// Connects to the signal emitted when the clock moves to another time.
+    pub fn connect_seeked<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "seeked",
+            false,
+            glib::closure_local!(move |obj: &Self| f(obj)),
+        )
+    }
+
+    /// Returns whether the clock is running.
+    pub fn is_playing(&self) -> bool {
+        self.imp().playing.get()
+    }
+
+    /// Starts the clock, its animations play.
+    pub fn play(&self) {
+        self.set_property("playing", true);
+    }
+
+    /// Stops the clock, its animations pause.
+    pub fn pause(&self) {
+        self.set_property("playing", false);
+    }
+}
@@ -0,0 +1,18 @@
+use gtk::glib;
+
+/// How the animation is fitted in the widget, like [`gtk::ContentFit`].
+///
+/// The animation is centred in the widget.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LottieContentFit")]
+pub enum ContentFit {
+    /// Stretches the animation to the widget, ignoring its aspect ratio.
+    Fill,
+    /// Scales the animation to fit in the widget, keeping its aspect ratio.
+    #[default]
+    Contain,
+    /// Scales the animation to cover the widget, the rest is clipped.
+    Cover,
+    /// Like [`ContentFit::Contain`], but never bigger than the default size.
+    ScaleDown,
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+use std::sync::Arc;
+
+use gtk::glib;
+
+/// Reasons why an animation could not be loaded.
+#[derive(Debug, Clone, glib::Boxed)]
+#[boxed_type(name = "LottieError", nullable)]
+pub enum Error {
+    /// The file or stream could not be read.
+    Io(glib::Error),
+    /// The data looked like a tgs sticker, but could not be decompressed.
+    Gzip(Arc<std::io::Error>),
+    /// The data is not a json document.
+    InvalidJson(String),
+    /// rlottie could not parse or render the animation, or its framerate is not positive.
+    Rejected,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "failed to read the data: {error}"),
+            Self::Gzip(error) => write!(f, "failed to decompress the data: {error}"),
+            Self::InvalidJson(reason) => write!(f, "invalid json: {reason}"),
+            Self::Rejected => f.write_str("rlottie could not parse the animation"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(error) => Some(error),
+            Self::Gzip(error) => Some(&**error),
+            _ => None,
+        }
+    }
+}
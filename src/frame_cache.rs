@@ -0,0 +1,614 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::rc::Weak;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::glib;
+
+use crate::loader::Source;
+use crate::loader::SourceKey;
+use crate::overrides::Overrides;
+use crate::render_pool::AnimationInfo;
+use crate::render_pool::RenderInfo;
+use crate::render_pool::Renderer;
+use crate::Error;
+
+/// Default limit of memory used by cached frames.
+pub const DEFAULT_CACHE_BUDGET: usize = 256 * 1024 * 1024;
+
+thread_local! {
+    static ANIMATIONS: RefCell<HashMap<AnimationKey, Weak<SharedAnimation>>> = Default::default();
+    static CACHE_USAGE: RefCell<CacheUsage> = const {
+        RefCell::new(CacheUsage {
+            usage: 0,
+            budget: DEFAULT_CACHE_BUDGET,
+            next_stamp: 0,
+            frames: BTreeMap::new(),
+        })
+    };
+}
+
+/// Sets how many bytes cached frames may use in total.
+///
+/// When the budget is exceeded, frames that weren't drawn
+/// for the longest time are dropped, whatever animation they belong to.
+/// Caches live on the main thread, so call it from there.
+pub fn set_cache_budget(bytes: usize) {
+    CACHE_USAGE.with_borrow_mut(|cache_usage| cache_usage.budget = bytes);
+    evict_frames();
+}
+
+/// Returns how many bytes cached frames may use in total.
+pub fn cache_budget() -> usize {
+    CACHE_USAGE.with_borrow(|cache_usage| cache_usage.budget)
+}
+
+/// Returns how many bytes cached frames use now.
+pub fn cache_usage() -> usize {
+    CACHE_USAGE.with_borrow(|cache_usage| cache_usage.usage)
+}
+
+#[derive(Debug)]
+struct CachedFrameInfo {
+    frame_cache: Weak<FrameCache>,
+    frame_num: usize,
+    bytes: usize,
+}
+
+/// Memory used by all frame caches.
+///
+/// Frames are ordered by stamps, the oldest stamp is the least recently used frame.
+#[derive(Debug)]
+struct CacheUsage {
+    usage: usize,
+    budget: usize,
+    next_stamp: u64,
+    frames: BTreeMap<u64, CachedFrameInfo>,
+}
+
+impl CacheUsage {
+    fn add(&mut self, info: CachedFrameInfo) -> u64 {
+        let stamp = self.next_stamp;
+        self.next_stamp += 1;
+
+        self.usage += info.bytes;
+        self.frames.insert(stamp, info);
+
+        stamp
+    }
+
+    fn remove(&mut self, stamp: u64) -> Option<CachedFrameInfo> {
+        let info = self.frames.remove(&stamp)?;
+        self.usage -= info.bytes;
+        Some(info)
+    }
+
+    /// Marks the frame as recently used, returns its new stamp.
+    fn touch(&mut self, stamp: u64) -> u64 {
+        match self.remove(stamp) {
+            Some(info) => self.add(info),
+            None => stamp,
+        }
+    }
+
+    fn pop_over_budget(&mut self) -> Option<(u64, CachedFrameInfo)> {
+        if self.usage <= self.budget {
+            return None;
+        }
+
+        let (stamp, info) = self.frames.pop_first()?;
+        self.usage -= info.bytes;
+        Some((stamp, info))
+    }
+}
+
+/// Drops the least recently used frames until they fit the budget.
+fn evict_frames() {
+    while let Some((stamp, info)) = CACHE_USAGE.with_borrow_mut(CacheUsage::pop_over_budget) {
+        if let Some(frame_cache) = info.frame_cache.upgrade() {
+            let mut frames = frame_cache.frames.borrow_mut();
+
+            if let Some(frame) = frames.get_mut(info.frame_num) {
+                if frame.as_ref().is_some_and(|frame| frame.stamp == stamp) {
+                    *frame = None;
+                }
+            }
+        }
+    }
+}
+
+/// Animations are shared when they show the same data with the same overrides.
+type AnimationKey = (SourceKey, Overrides);
+
+#[derive(Debug)]
+enum LoadState {
+    Loading(Vec<async_channel::Sender<Result<AnimationInfo, Error>>>),
+    Done(Result<AnimationInfo, Error>),
+}
+
+/// Frame number and pixel size of a render.
+type RenderKey = (usize, (i32, i32));
+
+/// Render of a frame that paintables wait for.
+#[derive(Debug)]
+struct PendingRender {
+    waiters: Vec<async_channel::Sender<Option<gdk::MemoryTexture>>>,
+    /// Set once nobody waits, the job is skipped then.
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Stops waiting for a render, cancels it when nobody else waits.
+struct RenderWaiter<'a> {
+    animation: &'a SharedAnimation,
+    key: RenderKey,
+    receiver: async_channel::Receiver<Option<gdk::MemoryTexture>>,
+}
+
+impl Drop for RenderWaiter<'_> {
+    fn drop(&mut self) {
+        self.receiver.close();
+
+        let mut pending_renders = self.animation.pending_renders.borrow_mut();
+
+        if let Some(pending) = pending_renders.get_mut(&self.key) {
+            pending.waiters.retain(|waiter| !waiter.is_closed());
+
+            if pending.waiters.is_empty() {
+                pending.cancelled.store(true, Ordering::Relaxed);
+                pending_renders.remove(&self.key);
+            }
+        }
+    }
+}
+
+/// Parsed animation shared by all paintables that show the same data.
+///
+/// It's freed with the last paintable that uses it.
+#[derive(Debug)]
+pub(crate) struct SharedAnimation {
+    key: AnimationKey,
+    renderer: Renderer,
+    state: RefCell<LoadState>,
+    frame_caches: RefCell<HashMap<(i32, i32), Weak<FrameCache>>>,
+    pending_renders: RefCell<HashMap<RenderKey, PendingRender>>,
+}
+
+impl SharedAnimation {
+    /// Returns the animation for the source, loads it if nobody uses it yet.
+    pub(crate) fn for_source(source: Source, overrides: &Overrides) -> Rc<Self> {
+        let key = (source.key(), overrides.clone());
+
+        if let Some(animation) = ANIMATIONS.with_borrow(|map| map.get(&key).and_then(Weak::upgrade))
+        {
+            return animation;
+        }
+
+        let animation = Rc::new(Self {
+            key: key.clone(),
+            renderer: Renderer::default(),
+            state: RefCell::new(LoadState::Loading(Vec::new())),
+            frame_caches: Default::default(),
+            pending_renders: Default::default(),
+        });
+
+        let (sender, receiver) = async_channel::bounded(1);
+
+        animation.renderer.load(source, overrides.clone(), sender);
+
+        glib::spawn_future_local(clone!(
+            #[weak]
+            animation,
+            async move {
+                // the render thread dropped the job
+                let result = receiver.recv().await.unwrap_or(Err(Error::Rejected));
+
+                // don't keep animations that failed, next open may succeed
+                if result.is_err() {
+                    ANIMATIONS.with_borrow_mut(|map| map.remove(&animation.key));
+                }
+
+                let state = animation.state.replace(LoadState::Done(result.clone()));
+
+                if let LoadState::Loading(waiters) = state {
+                    for waiter in waiters {
+                        _ = waiter.try_send(result.clone());
+                    }
+                }
+            }
+        ));
+
+        ANIMATIONS.with_borrow_mut(|map| map.insert(key, Rc::downgrade(&animation)));
+
+        animation
+    }
+
+    /// Waits until the animation is loaded.
+    pub(crate) async fn info(&self) -> Result<AnimationInfo, Error> {
+        let receiver = match &mut *self.state.borrow_mut() {
+            LoadState::Done(result) => return result.clone(),
+            LoadState::Loading(waiters) => {
+                let (sender, receiver) = async_channel::bounded(1);
+                waiters.push(sender);
+                receiver
+            }
+        };
+
+        receiver
+            .recv()
+            .await
+            .unwrap_or_else(|_| Err(Error::Rejected))
+    }
+
+    /// Renders the frame without caching it.
+    ///
+    /// Frame is rendered once even if many paintables wait for it,
+    /// the render is cancelled when all of them stop waiting.
+    pub(crate) async fn render(
+        self: &Rc<Self>,
+        frame_num: usize,
+        size: (i32, i32),
+    ) -> Option<gdk::MemoryTexture> {
+        let key = (frame_num, size);
+        let (sender, receiver) = async_channel::bounded(1);
+
+        let cancelled = {
+            let mut pending_renders = self.pending_renders.borrow_mut();
+
+            match pending_renders.get_mut(&key) {
+                Some(pending) => {
+                    pending.waiters.push(sender);
+                    None
+                }
+                None => {
+                    let cancelled = Arc::new(AtomicBool::new(false));
+
+                    pending_renders.insert(
+                        key,
+                        PendingRender {
+                            waiters: vec![sender],
+                            cancelled: cancelled.clone(),
+                        },
+                    );
+
+                    Some(cancelled)
+                }
+            }
+        };
+
+        if let Some(cancelled) = cancelled {
+            let (sender, receiver) = async_channel::bounded(1);
+
+            let (width, height) = size;
+
+            self.renderer.render(RenderInfo {
+                frame_num,
+                width,
+                height,
+                sender,
+                cancelled: cancelled.clone(),
+            });
+
+            glib::spawn_future_local(clone!(
+                #[weak(rename_to = animation)]
+                self,
+                async move {
+                    let texture = receiver.recv().await.ok().map(|(_, texture)| texture);
+
+                    if texture.is_none() && animation.renderer.is_failed() {
+                        animation.fail();
+                    }
+
+                    let mut pending_renders = animation.pending_renders.borrow_mut();
+
+                    // it was cancelled, and maybe requested again since
+                    if !pending_renders
+                        .get(&key)
+                        .is_some_and(|pending| Arc::ptr_eq(&pending.cancelled, &cancelled))
+                    {
+                        return;
+                    }
+
+                    let pending = pending_renders.remove(&key).unwrap();
+                    drop(pending_renders);
+
+                    for waiter in pending.waiters {
+                        _ = waiter.try_send(texture.clone());
+                    }
+                }
+            ));
+        }
+
+        let waiter = RenderWaiter {
+            animation: self,
+            key,
+            receiver,
+        };
+
+        waiter.receiver.recv().await.ok().flatten()
+    }
+
+    /// Returns the error if the animation failed to load or render.
+    pub(crate) fn error(&self) -> Option<Error> {
+        match &*self.state.borrow() {
+            LoadState::Done(Err(error)) => Some(error.clone()),
+            _ => None,
+        }
+    }
+
+    /// Marks the animation as failed after rendering crashed.
+    ///
+    /// Next open parses it again.
+    fn fail(&self) {
+        ANIMATIONS.with_borrow_mut(|map| {
+            if map
+                .get(&self.key)
+                .is_some_and(|weak| std::ptr::eq(weak.as_ptr(), self))
+            {
+                map.remove(&self.key);
+            }
+        });
+
+        self.state.replace(LoadState::Done(Err(Error::Rejected)));
+    }
+
+    /// Returns cache of frames with the given pixel size.
+    ///
+    /// Caches are shared too, and freed when nobody uses them.
+    pub(crate) fn frame_cache(self: &Rc<Self>, size: (i32, i32)) -> Rc<FrameCache> {
+        let mut frame_caches = self.frame_caches.borrow_mut();
+
+        if let Some(frame_cache) = frame_caches.get(&size).and_then(Weak::upgrade) {
+            return frame_cache;
+        }
+
+        let totalframe = match &*self.state.borrow() {
+            LoadState::Done(Ok(info)) => info.totalframe,
+            _ => 0,
+        };
+
+        let frame_cache = Rc::new(FrameCache {
+            animation: self.clone(),
+            size,
+            frames: RefCell::new((0..totalframe).map(|_| None).collect()),
+            pending: Default::default(),
+        });
+
+        frame_caches.retain(|_, frame_cache| frame_cache.strong_count() > 0);
+        frame_caches.insert(size, Rc::downgrade(&frame_cache));
+
+        frame_cache
+    }
+}
+
+impl Drop for SharedAnimation {
+    fn drop(&mut self) {
+        ANIMATIONS.with_borrow_mut(|map| {
+            if map
+                .get(&self.key)
+                .is_some_and(|weak| weak.strong_count() == 0)
+            {
+                map.remove(&self.key);
+            }
+        });
+    }
+}
+
+#[derive(Debug)]
+struct CachedFrame {
+    texture: gdk::MemoryTexture,
+    stamp: u64,
+}
+
+/// Rendered frames of a shared animation in a single pixel size.
+#[derive(Debug)]
+pub(crate) struct FrameCache {
+    animation: Rc<SharedAnimation>,
+    size: (i32, i32),
+    frames: RefCell<Vec<Option<CachedFrame>>>,
+    pending: RefCell<HashMap<usize, Vec<async_channel::Sender<Option<gdk::MemoryTexture>>>>>,
+}
+
+impl FrameCache {
+    pub(crate) fn size(&self) -> (i32, i32) {
+        self.size
+    }
+
+    /// Returns the frame if it's already rendered.
+    pub(crate) fn cached_frame(&self, frame_num: usize) -> Option<gdk::MemoryTexture> {
+        let mut frames = self.frames.borrow_mut();
+        let frame = frames.get_mut(frame_num)?.as_mut()?;
+
+        frame.stamp = CACHE_USAGE.with_borrow_mut(|cache_usage| cache_usage.touch(frame.stamp));
+
+        Some(frame.texture.clone())
+    }
+
+    fn store(self: &Rc<Self>, frame_num: usize, texture: &gdk::MemoryTexture) {
+        if frame_num >= self.frames.borrow().len() {
+            return;
+        }
+
+        let (width, height) = self.size;
+
+        let stamp = CACHE_USAGE.with_borrow_mut(|cache_usage| {
+            cache_usage.add(CachedFrameInfo {
+                frame_cache: Rc::downgrade(self),
+                frame_num,
+                bytes: width as usize * height as usize * 4,
+            })
+        });
+
+        let previous = self.frames.borrow_mut()[frame_num].replace(CachedFrame {
+            texture: texture.clone(),
+            stamp,
+        });
+
+        if let Some(previous) = previous {
+            CACHE_USAGE.with_borrow_mut(|cache_usage| cache_usage.remove(previous.stamp));
+        }
+
+        evict_frames();
+    }
+
+    /// Returns the frame, renders it if needed.
+    ///
+    /// Frame is rendered once even if many paintables wait for it.
+    pub(crate) async fn frame(self: Rc<Self>, frame_num: usize) -> Option<gdk::MemoryTexture> {
+        if let Some(texture) = self.cached_frame(frame_num) {
+            return Some(texture);
+        }
+
+        let (sender, receiver) = async_channel::bounded(1);
+
+        let is_first_request = {
+            let mut pending = self.pending.borrow_mut();
+            let waiters = pending.entry(frame_num).or_default();
+            waiters.push(sender);
+            waiters.len() == 1
+        };
+
+        if is_first_request {
+            glib::spawn_future_local(clone!(
+                #[strong(rename_to = frame_cache)]
+                self,
+                async move {
+                    let texture = frame_cache
+                        .animation
+                        .render(frame_num, frame_cache.size)
+                        .await;
+
+                    if let Some(texture) = &texture {
+                        frame_cache.store(frame_num, texture);
+                    }
+
+                    let waiters = frame_cache.pending.borrow_mut().remove(&frame_num);
+
+                    for waiter in waiters.unwrap_or_default() {
+                        _ = waiter.try_send(texture.clone());
+                    }
+                }
+            ));
+        }
+
+        receiver.recv().await.ok().flatten()
+    }
+}
+
+impl Drop for FrameCache {
+    fn drop(&mut self) {
+        let frames = self.frames.get_mut();
+
+        _ = CACHE_USAGE.try_with(|cache_usage| {
+            let mut cache_usage = cache_usage.borrow_mut();
+
+            for frame in frames.iter().flatten() {
+                cache_usage.remove(frame.stamp);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_usage(budget: usize) -> CacheUsage {
+        CacheUsage {
+            usage: 0,
+            budget,
+            next_stamp: 0,
+            frames: BTreeMap::new(),
+        }
+    }
+
+    fn frame(frame_num: usize, bytes: usize) -> CachedFrameInfo {
+        CachedFrameInfo {
+            frame_cache: Weak::new(),
+            frame_num,
+            bytes,
+        }
+    }
+
+    /// Pops frames over the budget, returns their numbers.
+    fn evicted(cache_usage: &mut CacheUsage) -> Vec<usize> {
+        std::iter::from_fn(|| cache_usage.pop_over_budget())
+            .map(|(_, info)| info.frame_num)
+            .collect()
+    }
+
+    #[test]
+    fn usage_accounting() {
+        let mut cache_usage = cache_usage(1000);
+
+        let first = cache_usage.add(frame(0, 100));
+        let second = cache_usage.add(frame(1, 200));
+        assert_eq!(cache_usage.usage, 300);
+
+        let second = cache_usage.touch(second);
+        assert_eq!(cache_usage.usage, 300);
+
+        assert_eq!(
+            cache_usage.remove(first).map(|info| info.frame_num),
+            Some(0)
+        );
+        assert_eq!(cache_usage.usage, 200);
+
+        // removed twice
+        assert!(cache_usage.remove(first).is_none());
+        assert_eq!(cache_usage.usage, 200);
+
+        cache_usage.remove(second);
+        assert_eq!(cache_usage.usage, 0);
+        assert!(cache_usage.frames.is_empty());
+    }
+
+    #[test]
+    fn touch_unknown_stamp() {
+        let mut cache_usage = cache_usage(1000);
+        assert_eq!(cache_usage.touch(42), 42);
+        assert_eq!(cache_usage.usage, 0);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let mut cache_usage = cache_usage(300);
+
+        let first = cache_usage.add(frame(0, 100));
+        cache_usage.add(frame(1, 100));
+        cache_usage.add(frame(2, 100));
+        assert!(evicted(&mut cache_usage).is_empty());
+
+        // first frame is drawn again, the second one is the oldest now
+        cache_usage.touch(first);
+        cache_usage.add(frame(3, 100));
+
+        assert_eq!(evicted(&mut cache_usage), [1]);
+        assert_eq!(cache_usage.usage, 300);
+    }
+
+    #[test]
+    fn lowering_budget() {
+        let mut cache_usage = cache_usage(1000);
+
+        for frame_num in 0..5 {
+            cache_usage.add(frame(frame_num, 100));
+        }
+
+        cache_usage.budget = 250;
+
+        assert_eq!(evicted(&mut cache_usage), [0, 1, 2]);
+        assert_eq!(cache_usage.usage, 200);
+
+        cache_usage.budget = 0;
+
+        assert_eq!(evicted(&mut cache_usage), [3, 4]);
+        assert_eq!(cache_usage.usage, 0);
+        assert!(cache_usage.pop_over_budget().is_none());
+    }
+}
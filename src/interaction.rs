@@ -0,0 +1,18 @@
+use gtk::glib;
+
+/// How the widget reacts to the pointer.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LottieInteraction")]
+pub enum Interaction {
+    /// Playback is controlled by the app only.
+    #[default]
+    None,
+    /// Plays while the pointer is over the widget.
+    PlayOnHover,
+    /// Plays on click, from the start if playback ended.
+    PlayOnClick,
+    /// Plays or pauses on click.
+    ToggleOnClick,
+    /// Plays from the start on click.
+    RestartOnClick,
+}
@@ -0,0 +1,121 @@
+use std::io::Read;
+use std::sync::Arc;
+
+use flate2::read::GzDecoder;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+
+use crate::marker;
+use crate::Error;
+use crate::Marker;
+
+/// First bytes of every gzip stream, tgs stickers are gzipped json.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Where the animation data comes from.
+#[derive(Debug, Clone)]
+pub(crate) enum Source {
+    File(gio::File),
+    Bytes {
+        data: glib::Bytes,
+        cache_key: String,
+    },
+}
+
+/// Identity of the animation data.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum SourceKey {
+    Uri(String),
+    CacheKey(String),
+}
+
+impl Source {
+    /// Returns the identity of the data, used to share parsed animations.
+    pub(crate) fn key(&self) -> SourceKey {
+        match self {
+            Source::File(file) => SourceKey::Uri(file.uri().into()),
+            Source::Bytes { cache_key, .. } => SourceKey::CacheKey(cache_key.clone()),
+        }
+    }
+}
+
+/// Loads the animation with its markers, this is blocking so call it from a thread.
+pub(crate) fn load_animation(source: Source) -> Result<(rlottie::Animation, Vec<Marker>), Error> {
+    match source {
+        Source::File(file) => {
+            if let Some(path) = file.path() {
+                if let Some(animation) = rlottie::Animation::from_file(&path) {
+                    let markers = std::fs::read(&path)
+                        .ok()
+                        .and_then(|json| marker::parse_markers(&json).ok())
+                        .unwrap_or_default();
+
+                    return Ok((animation, markers));
+                }
+            }
+
+            let data = file
+                .load_contents(gio::Cancellable::NONE)
+                .map_err(Error::Io)?
+                .0;
+
+            from_data(&data, file.uri().into())
+        }
+        Source::Bytes { data, cache_key } => from_data(&data, cache_key),
+    }
+}
+
+/// Parses json or tgs data.
+///
+/// `cache_key` is used by rlottie to cache the parsed animation,
+/// so different data must have different keys.
+fn from_data(data: &[u8], cache_key: String) -> Result<(rlottie::Animation, Vec<Marker>), Error> {
+    let json = if data.starts_with(&GZIP_MAGIC) {
+        let mut gz = GzDecoder::new(data);
+        let mut buf = Vec::new();
+
+        gz.read_to_end(&mut buf)
+            .map_err(|error| Error::Gzip(Arc::new(error)))?;
+
+        buf
+    } else {
+        data.to_owned()
+    };
+
+    let json = String::from_utf8(json).map_err(|error| Error::InvalidJson(error.to_string()))?;
+
+    if json.contains('\0') {
+        return Err(Error::InvalidJson("unexpected nul byte".to_owned()));
+    }
+
+    // rlottie doesn't tell why it fails, so the syntax is checked with markers
+    let markers = marker::parse_markers(json.as_bytes())
+        .map_err(|error| Error::InvalidJson(error.to_string()))?;
+
+    let animation = rlottie::Animation::from_data(json, cache_key, "").ok_or(Error::Rejected)?;
+
+    Ok((animation, markers))
+}
+
+/// Reads the whole stream.
+pub(crate) async fn read_stream(stream: &gio::InputStream) -> Result<glib::Bytes, Error> {
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    let mut data = Vec::new();
+
+    loop {
+        let chunk = stream
+            .read_bytes_future(CHUNK_SIZE, glib::Priority::DEFAULT)
+            .await
+            .map_err(Error::Io)?;
+
+        if chunk.is_empty() {
+            break;
+        }
+
+        data.extend_from_slice(&chunk);
+    }
+
+    Ok(glib::Bytes::from_owned(data))
+}
@@ -0,0 +1,146 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Deserializer;
+
+/// Named part of the animation, designers use markers for states like "idle" or "hover".
+///
+/// Frames are in the same numbering as [`crate::AnimationPaintable::set_segment`],
+/// `end` is not included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Default, Deserialize)]
+struct RawAnimation {
+    #[serde(default, deserialize_with = "lenient")]
+    ip: f64,
+    #[serde(default, deserialize_with = "lenient")]
+    markers: Vec<RawMarker>,
+}
+
+#[derive(Default, Deserialize)]
+struct RawMarker {
+    #[serde(default, deserialize_with = "lenient")]
+    cm: String,
+    #[serde(default, deserialize_with = "lenient")]
+    tm: f64,
+    #[serde(default, deserialize_with = "lenient")]
+    dr: f64,
+}
+
+/// Deserializes the value, or the default if it has another type.
+///
+/// Parsing goes on, so the rest of the json is still checked.
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    Ok(T::deserialize(value).unwrap_or_default())
+}
+
+/// Reads markers from the json, rlottie doesn't give them to us.
+///
+/// It's the only pass over the json before rlottie, so broken syntax is an error.
+/// Broken markers are no reason to reject the animation, so they're just empty.
+pub(crate) fn parse_markers(json: &[u8]) -> Result<Vec<Marker>, serde_json::Error> {
+    let animation = match serde_json::from_slice::<RawAnimation>(json) {
+        Ok(animation) => animation,
+        // valid json, but not an object, rlottie rejects it
+        Err(error) if error.is_data() => RawAnimation::default(),
+        Err(error) => return Err(error),
+    };
+
+    let markers = animation
+        .markers
+        .into_iter()
+        .map(|marker| {
+            let start = (marker.tm - animation.ip).max(0.0) as usize;
+            let end = start.saturating_add((marker.dr.max(0.0) as usize).max(1));
+
+            Marker {
+                name: marker.cm,
+                start,
+                end,
+            }
+        })
+        .collect();
+
+    Ok(markers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn marker(name: &str, start: usize, end: usize) -> Marker {
+        Marker {
+            name: name.to_owned(),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn relative_to_in_point() {
+        let json = br#"{"ip": 10, "markers": [{"cm": "idle", "tm": 30, "dr": 20}]}"#;
+        assert_eq!(parse_markers(json).unwrap(), [marker("idle", 20, 40)]);
+    }
+
+    #[test]
+    fn missing_in_point() {
+        let json = br#"{"markers": [{"cm": "hover", "tm": 5, "dr": 10}]}"#;
+        assert_eq!(parse_markers(json).unwrap(), [marker("hover", 5, 15)]);
+    }
+
+    #[test]
+    fn negative_duration() {
+        let json = br#"{"markers": [{"cm": "press", "tm": 5, "dr": -3}]}"#;
+        assert_eq!(parse_markers(json).unwrap(), [marker("press", 5, 6)]);
+    }
+
+    #[test]
+    fn before_in_point() {
+        let json = br#"{"ip": 10, "markers": [{"cm": "intro", "tm": 2, "dr": 4}]}"#;
+        assert_eq!(parse_markers(json).unwrap(), [marker("intro", 0, 4)]);
+    }
+
+    #[test]
+    fn huge_time() {
+        let json = br#"{"markers": [{"cm": "far", "tm": 1e300, "dr": 1}]}"#;
+        assert_eq!(
+            parse_markers(json).unwrap(),
+            [marker("far", usize::MAX, usize::MAX)]
+        );
+    }
+
+    #[test]
+    fn no_markers() {
+        assert_eq!(parse_markers(br#"{"ip": 0, "op": 60}"#).unwrap(), []);
+    }
+
+    #[test]
+    fn malformed_json() {
+        assert!(parse_markers(br#"{"markers": [{"cm": "idle", "tm": 3"#).is_err());
+        assert!(parse_markers(b"not json").is_err());
+        assert!(parse_markers(b"").is_err());
+    }
+
+    #[test]
+    fn malformed_after_wrong_type() {
+        assert!(parse_markers(br#"{"markers": "idle", "layers": [}"#).is_err());
+    }
+
+    #[test]
+    fn wrong_types() {
+        assert_eq!(parse_markers(br#"{"markers": "idle"}"#).unwrap(), []);
+        assert_eq!(parse_markers(br#"[1, 2]"#).unwrap(), []);
+
+        let json = br#"{"ip": "x", "markers": [{"cm": 5, "tm": 3, "dr": "long"}]}"#;
+        assert_eq!(parse_markers(json).unwrap(), [marker("", 3, 4)]);
+    }
+}
@@ -0,0 +1,256 @@
+use std::cell::OnceCell;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use crate::AnimationPaintable;
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, Debug)]
+    pub struct AnimationMediaStream {
+        pub(super) paintable: OnceCell<AnimationPaintable>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AnimationMediaStream {
+        const NAME: &'static str = "LottieAnimationMediaStream";
+        type Type = super::AnimationMediaStream;
+        type ParentType = gtk::MediaStream;
+    }
+
+    impl ObjectImpl for AnimationMediaStream {
+        fn constructed(&self) {
+            self.parent_constructed();
+
+            let obj = self.obj();
+            let paintable = obj.paintable();
+
+            paintable.connect_invalidate_contents(clone!(
+                #[weak]
+                obj,
+                move |_| {
+                    obj.invalidate_contents();
+                    obj.update(obj.imp().timestamp());
+                }
+            ));
+
+            paintable.connect_invalidate_size(clone!(
+                #[weak]
+                obj,
+                move |_| {
+                    obj.invalidate_size();
+                    obj.imp().prepare();
+                }
+            ));
+
+            paintable.connect_load_failed(clone!(
+                #[weak]
+                obj,
+                move |_, error| {
+                    obj.set_error(glib::Error::new(
+                        gio::IOErrorEnum::Failed,
+                        &error.to_string(),
+                    ));
+                }
+            ));
+
+            paintable.connect_notify_local(
+                Some("playing"),
+                clone!(
+                    #[weak]
+                    obj,
+                    move |paintable, _| {
+                        if paintable.is_playing() {
+                            obj.play();
+                        } else {
+                            obj.pause();
+                        }
+                    }
+                ),
+            );
+
+            paintable.connect_ended(clone!(
+                #[weak]
+                obj,
+                move |_| {
+                    if obj.is_prepared() {
+                        #[cfg(feature = "v4_12")]
+                        obj.stream_ended();
+                        #[cfg(not(feature = "v4_12"))]
+                        obj.ended();
+                    }
+                }
+            ));
+
+            paintable.connect_notify_local(
+                Some("loop"),
+                clone!(
+                    #[weak]
+                    obj,
+                    move |paintable, _| obj.set_loop(paintable.is_loop())
+                ),
+            );
+
+            obj.connect_loop_notify(|obj| obj.paintable().set_loop(obj.is_loop()));
+
+            obj.set_loop(paintable.is_loop());
+
+            self.prepare();
+        }
+
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: OnceLock<Vec<glib::ParamSpec>> = OnceLock::new();
+            PROPERTIES.get_or_init(|| {
+                vec![
+                    glib::ParamSpecObject::builder::<AnimationPaintable>("paintable")
+                        .construct_only()
+                        .build(),
+                ]
+            })
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "paintable" => self.obj().paintable().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            match pspec.name() {
+                "paintable" => {
+                    let paintable = value
+                        .get::<Option<AnimationPaintable>>()
+                        .unwrap()
+                        .unwrap_or_default();
+                    self.paintable.set(paintable).unwrap();
+                }
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl MediaStreamImpl for AnimationMediaStream {
+        fn play(&self) -> bool {
+            let obj = self.obj();
+            let paintable = obj.paintable();
+
+            if obj.is_ended() {
+                paintable.rewind();
+            }
+
+            paintable.play();
+            true
+        }
+
+        fn pause(&self) {
+            self.obj().paintable().pause();
+        }
+
+        fn seek(&self, timestamp: i64) {
+            let obj = self.obj();
+
+            obj.paintable()
+                .seek(Duration::from_micros(timestamp.max(0) as u64));
+
+            obj.seek_success();
+            obj.update(timestamp);
+        }
+    }
+
+    impl PaintableImpl for AnimationMediaStream {
+        fn snapshot(&self, snapshot: &gdk::Snapshot, width: f64, height: f64) {
+            self.obj().paintable().snapshot(snapshot, width, height);
+        }
+
+        fn current_image(&self) -> gdk::Paintable {
+            self.obj().paintable().current_image()
+        }
+
+        fn intrinsic_width(&self) -> i32 {
+            self.obj().paintable().intrinsic_width()
+        }
+
+        fn intrinsic_height(&self) -> i32 {
+            self.obj().paintable().intrinsic_height()
+        }
+
+        fn intrinsic_aspect_ratio(&self) -> f64 {
+            self.obj().paintable().intrinsic_aspect_ratio()
+        }
+    }
+
+    impl AnimationMediaStream {
+        /// Marks the stream as prepared once the animation is loaded.
+        ///
+        /// It's prepared again when another animation is opened.
+        fn prepare(&self) {
+            let obj = self.obj();
+            let duration = obj.paintable().duration().as_micros() as i64;
+
+            if obj.is_prepared() {
+                if obj.duration() == duration {
+                    return;
+                }
+
+                #[cfg(feature = "v4_12")]
+                obj.stream_unprepared();
+                #[cfg(not(feature = "v4_12"))]
+                obj.unprepared();
+            }
+
+            if duration > 0 {
+                #[cfg(feature = "v4_12")]
+                obj.stream_prepared(false, true, true, duration);
+                #[cfg(not(feature = "v4_12"))]
+                obj.prepared(false, true, true, duration);
+            }
+        }
+
+        fn timestamp(&self) -> i64 {
+            self.obj().paintable().position().as_micros() as i64
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Media stream that plays vector lottie animation
+    ///
+    /// Use it with [`gtk::Video`] or [`gtk::MediaControls`]
+    /// to get the standard media controls.
+    pub struct AnimationMediaStream(ObjectSubclass<imp::AnimationMediaStream>)
+        @extends gtk::MediaStream,
+        @implements gdk::Paintable;
+}
+
+impl AnimationMediaStream {
+    /// Creates media stream that plays the paintable.
+    pub fn new(paintable: &AnimationPaintable) -> Self {
+        glib::Object::builder()
+            .property("paintable", paintable)
+            .build()
+    }
+
+    /// Creates media stream from json or tgs file.
+    pub fn from_file(file: &impl IsA<gio::File>) -> Self {
+        Self::new(&AnimationPaintable::from_file(file))
+    }
+
+    /// Creates media stream from json or tgs file from the given filename.
+    pub fn from_filename(path: &str) -> Self {
+        Self::new(&AnimationPaintable::from_filename(path))
+    }
+
+    /// Returns the paintable that renders the animation.
+    pub fn paintable(&self) -> AnimationPaintable {
+        self.imp().paintable.get().unwrap().clone()
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use std::hash::Hasher;
+
+use gtk::gdk;
+
+/// Layer property that can be changed at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum Property {
+    FillColor,
+    FillOpacity,
+    StrokeColor,
+    StrokeOpacity,
+    StrokeWidth,
+}
+
+/// Property values set by keypath, applied to the animation after it's parsed.
+///
+/// They are part of the identity of shared animations,
+/// so animations with different overrides don't share frames.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Overrides {
+    /// Color of all fills and strokes, keypaths override it.
+    tint: Option<[f64; 3]>,
+    values: BTreeMap<(String, Property), [f64; 3]>,
+}
+
+impl Overrides {
+    pub(crate) fn set_tint(&mut self, color: Option<&gdk::RGBA>) {
+        self.tint = color.map(rgb);
+    }
+
+    pub(crate) fn set_color(&mut self, keypath: &str, property: Property, color: &gdk::RGBA) {
+        self.values
+            .insert((keypath.to_owned(), property), rgb(color));
+    }
+
+    /// Sets opacity of fills and strokes, from 0 to 1.
+    pub(crate) fn set_opacity(&mut self, keypath: &str, opacity: f64) {
+        // rlottie opacity goes from 0 to 100
+        let value = [sanitize(opacity).clamp(0.0, 1.0) * 100.0, 0.0, 0.0];
+        self.values
+            .insert((keypath.to_owned(), Property::FillOpacity), value);
+        self.values
+            .insert((keypath.to_owned(), Property::StrokeOpacity), value);
+    }
+
+    pub(crate) fn set_stroke_width(&mut self, keypath: &str, width: f64) {
+        let value = [sanitize(width).max(0.0), 0.0, 0.0];
+        self.values
+            .insert((keypath.to_owned(), Property::StrokeWidth), value);
+    }
+
+    /// Removes all overrides of the keypath.
+    pub(crate) fn clear(&mut self, keypath: &str) {
+        self.values.retain(|(path, _), _| path != keypath);
+    }
+
+    /// Applies the overrides, call it from the render thread.
+    pub(crate) fn apply(&self, animation: &mut rlottie::Animation) {
+        if let Some([r, g, b]) = self.tint {
+            animation.set_fill_color("**", rlottie::Rgb::new(r, g, b));
+            animation.set_stroke_color("**", rlottie::Rgb::new(r, g, b));
+        }
+
+        for ((keypath, property), [a, b, c]) in &self.values {
+            match property {
+                Property::FillColor => {
+                    animation.set_fill_color(keypath, rlottie::Rgb::new(*a, *b, *c))
+                }
+                Property::FillOpacity => animation.set_fill_opacity(keypath, *a),
+                Property::StrokeColor => {
+                    animation.set_stroke_color(keypath, rlottie::Rgb::new(*a, *b, *c))
+                }
+                Property::StrokeOpacity => animation.set_stroke_opacity(keypath, *a),
+                Property::StrokeWidth => animation.set_stroke_width(keypath, *a),
+            }
+        }
+    }
+}
+
+// values are never NaN or -0, so equal values have equal bits
+impl Eq for Overrides {}
+
+impl Hash for Overrides {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.tint.map(|tint| tint.map(f64::to_bits)).hash(state);
+
+        for (key, value) in &self.values {
+            key.hash(state);
+            value.map(f64::to_bits).hash(state);
+        }
+    }
+}
+
+fn rgb(color: &gdk::RGBA) -> [f64; 3] {
+    let channel = |value: f32| sanitize(value as f64).clamp(0.0, 1.0);
+    [
+        channel(color.red()),
+        channel(color.green()),
+        channel(color.blue()),
+    ]
+}
+
+/// Replaces values rlottie would reject, and -0 that hashes differently from 0.
+fn sanitize(value: f64) -> f64 {
+    if value.is_finite() && value != 0.0 {
+        value
+    } else {
+        0.0
+    }
+}
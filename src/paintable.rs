@@ -0,0 +1,1555 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use glib::clone;
+use gtk::gdk;
+use gtk::gio;
+use gtk::glib;
+use gtk::prelude::*;
+use gtk::subclass::prelude::*;
+
+use crate::frame_cache::FrameCache;
+use crate::frame_cache::SharedAnimation;
+use crate::loader;
+use crate::loader::Source;
+use crate::overrides::Overrides;
+use crate::overrides::Property;
+use crate::render_pool::AnimationInfo;
+use crate::timeline::Timeline;
+use crate::AnimationClock;
+use crate::Error;
+use crate::Marker;
+use crate::PlaybackMode;
+
+/// How often the playing animation checks for a new frame without a widget.
+const TICK_INTERVAL: Duration = Duration::from_micros(1_000_000 / 60);
+
+/// Drops the cache and stops ticking when shown frames aren't drawn for that long.
+const UNDRAWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Properties shared by [`AnimationPaintable`] and [`crate::Animation`].
+///
+/// The widget forwards them to its paintable.
+pub(crate) fn shared_properties() -> Vec<glib::ParamSpec> {
+    vec![
+        glib::ParamSpecObject::builder::<AnimationClock>("clock")
+            .explicit_notify()
+            .build(),
+        glib::ParamSpecBoxed::builder::<Error>("error")
+            .read_only()
+            .build(),
+        glib::ParamSpecInt::builder("default-height")
+            .read_only()
+            .build(),
+        glib::ParamSpecInt::builder("default-width")
+            .read_only()
+            .build(),
+        glib::ParamSpecInt64::builder("duration")
+            .read_only()
+            .build(),
+        glib::ParamSpecDouble::builder("framerate")
+            .read_only()
+            .build(),
+        glib::ParamSpecBoolean::builder("loaded")
+            .read_only()
+            .build(),
+        glib::ParamSpecUInt::builder("iteration")
+            .read_only()
+            .build(),
+        glib::ParamSpecBoolean::builder("loop")
+            .explicit_notify()
+            .build(),
+        glib::ParamSpecInt::builder("loop-count")
+            .minimum(-1)
+            .default_value(0)
+            .explicit_notify()
+            .build(),
+        glib::ParamSpecBoolean::builder("playing")
+            .explicit_notify()
+            .build(),
+        glib::ParamSpecEnum::builder::<PlaybackMode>("playback-mode")
+            .explicit_notify()
+            .build(),
+        glib::ParamSpecDouble::builder("progress")
+            .minimum(0.0)
+            .maximum(1.0)
+            .explicit_notify()
+            .build(),
+        glib::ParamSpecBoolean::builder("reversed")
+            .explicit_notify()
+            .build(),
+        glib::ParamSpecDouble::builder("speed")
+            .default_value(1.0)
+            .explicit_notify()
+            .build(),
+        glib::ParamSpecUInt::builder("total-frames")
+            .read_only()
+            .build(),
+        glib::ParamSpecBoolean::builder("use-cache")
+            .explicit_notify()
+            .build(),
+    ]
+}
+
+/// Signals shared by [`AnimationPaintable`] and [`crate::Animation`].
+///
+/// The widget re-emits them from its paintable.
+pub(crate) fn shared_signals() -> Vec<glib::subclass::Signal> {
+    vec![
+        glib::subclass::Signal::builder("load-failed")
+            .param_types([Error::static_type()])
+            .build(),
+        glib::subclass::Signal::builder("loaded").build(),
+        glib::subclass::Signal::builder("frame-changed")
+            .param_types([u32::static_type()])
+            .build(),
+        glib::subclass::Signal::builder("looped")
+            .param_types([u32::static_type()])
+            .build(),
+        glib::subclass::Signal::builder("ended").build(),
+    ]
+}
+
+/// Source of ticks while playing.
+#[derive(Debug)]
+enum Ticker {
+    /// Paintable drawn by the app itself.
+    Timer(glib::SourceId),
+    /// Frame clock of the widget showing the paintable.
+    FrameClock(gtk::TickCallbackId),
+}
+
+impl Ticker {
+    fn remove(self) {
+        match self {
+            Self::Timer(source) => source.remove(),
+            Self::FrameClock(id) => id.remove(),
+        }
+    }
+}
+
+/// Frame being rendered without the cache.
+#[derive(Debug)]
+struct RenderRequest {
+    handle: glib::JoinHandle<()>,
+    animation: Rc<SharedAnimation>,
+    size: (i32, i32),
+}
+
+mod imp {
+    use super::*;
+
+    #[derive(Default, Debug)]
+    pub struct AnimationPaintable {
+        pub(super) clock_start: Cell<i64>,
+        pub(super) start_distance: Cell<f64>,
+        pub(super) clock_iteration: Cell<i64>,
+        pub(super) loops: Cell<u32>,
+        pub(super) ticker: RefCell<Option<Ticker>>,
+        pub(super) clock: RefCell<Option<AnimationClock>>,
+        pub(super) clock_handlers: RefCell<Vec<glib::SignalHandlerId>>,
+        /// Widget showing the paintable, clock plays and pauses through it
+        /// and its frame clock drives playback.
+        pub(super) owner: glib::WeakRef<gtk::Widget>,
+
+        pub(super) source: RefCell<Option<Source>>,
+        pub(super) overrides: RefCell<Overrides>,
+        pub(super) animation: RefCell<Option<Rc<SharedAnimation>>>,
+        /// Animation with new overrides, replaces the current one once it's loaded.
+        pub(super) reloading: RefCell<Option<Rc<SharedAnimation>>>,
+        /// Reloads once for all overrides changed in a main loop iteration.
+        pub(super) reload_source: RefCell<Option<glib::SourceId>>,
+        pub(super) frame_cache: RefCell<Option<Rc<FrameCache>>>,
+        pub(super) render_request: RefCell<Option<RenderRequest>>,
+        pub(super) texture: RefCell<Option<gdk::MemoryTexture>>,
+        pub(super) shown_frame: Cell<Option<usize>>,
+        pub(super) frame_num: Cell<usize>,
+        pub(super) framerate: Cell<f64>,
+        pub(super) frame_delay: Cell<Duration>,
+        pub(super) totalframe: Cell<usize>,
+        pub(super) segment: Cell<Option<(usize, usize)>>,
+        pub(super) markers: RefCell<Vec<Marker>>,
+        /// When a frame was shown that wasn't drawn yet.
+        pub(super) undrawn_since: Cell<Option<std::time::Instant>>,
+        pub(super) default_size: Cell<(i32, i32)>,
+        pub(super) size: Cell<(f64, f64)>,
+        pub(super) scale_factor: Cell<i32>,
+
+        // fields for properties
+        pub(super) error: RefCell<Option<Error>>,
+        pub(super) loop_count: Cell<i32>,
+        pub(super) playing: Cell<bool>,
+        pub(super) playback_mode: Cell<PlaybackMode>,
+        pub(super) reversed: Cell<bool>,
+        pub(super) speed: Cell<f64>,
+        pub(super) use_cache: Cell<bool>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for AnimationPaintable {
+        const NAME: &'static str = "LottieAnimationPaintable";
+        type Type = super::AnimationPaintable;
+        type Interfaces = (gdk::Paintable,);
+    }
+
+    impl ObjectImpl for AnimationPaintable {
+        fn constructed(&self) {
+            self.parent_constructed();
+            self.scale_factor.set(1);
+            self.speed.set(1.0);
+        }
+
+        fn dispose(&self) {
+            self.stop_ticking();
+
+            if let Some(source) = self.reload_source.take() {
+                source.remove();
+            }
+
+            if let Some(request) = self.render_request.take() {
+                request.handle.abort();
+            }
+
+            self.disconnect_clock();
+        }
+
+        fn signals() -> &'static [glib::subclass::Signal] {
+            static SIGNALS: OnceLock<Vec<glib::subclass::Signal>> = OnceLock::new();
+            SIGNALS.get_or_init(shared_signals)
+        }
+
+        fn properties() -> &'static [glib::ParamSpec] {
+            static PROPERTIES: OnceLock<Vec<glib::ParamSpec>> = OnceLock::new();
+            PROPERTIES.get_or_init(shared_properties)
+        }
+
+        fn property(&self, _id: usize, pspec: &glib::ParamSpec) -> glib::Value {
+            match pspec.name() {
+                "clock" => self.clock.borrow().to_value(),
+                "default-height" => self.default_size.get().1.to_value(),
+                "default-width" => self.default_size.get().0.to_value(),
+                "duration" => (self.obj().duration().as_micros() as i64).to_value(),
+                "error" => self.error.borrow().to_value(),
+                "framerate" => self.framerate.get().to_value(),
+                "loaded" => (self.totalframe.get() > 0).to_value(),
+                "iteration" => self.loops.get().to_value(),
+                "loop" => (self.loop_count.get() != 0).to_value(),
+                "loop-count" => self.loop_count.get().to_value(),
+                "playing" => self.playing.get().to_value(),
+                "playback-mode" => self.playback_mode.get().to_value(),
+                "progress" => {
+                    let (first, last) = self.timeline().bounds();
+                    let frame = self.frame_num.get().clamp(first, last) - first;
+                    (frame as f64 / (last - first).max(1) as f64).to_value()
+                }
+                "reversed" => self.reversed.get().to_value(),
+                "speed" => self.speed.get().to_value(),
+                "total-frames" => (self.totalframe.get() as u32).to_value(),
+                "use-cache" => self.use_cache.get().to_value(),
+                _ => unimplemented!(),
+            }
+        }
+
+        fn set_property(&self, _id: usize, value: &glib::Value, pspec: &glib::ParamSpec) {
+            let obj = self.obj();
+
+            match pspec.name() {
+                "clock" => {
+                    let clock: Option<AnimationClock> = value.get().unwrap();
+                    if clock != *self.clock.borrow() {
+                        self.attach_clock(clock);
+                        obj.notify("clock");
+                    }
+                }
+                "loop" => {
+                    let loop_: bool = value.get().unwrap();
+                    if loop_ != (self.loop_count.get() != 0) {
+                        obj.set_loop_count(if loop_ { -1 } else { 0 });
+                    }
+                }
+                "loop-count" => {
+                    let loop_count = value.get().unwrap();
+                    let was_loop = self.loop_count.get() != 0;
+                    if self.loop_count.replace(loop_count) != loop_count {
+                        obj.notify("loop-count");
+
+                        if was_loop != (loop_count != 0) {
+                            obj.notify("loop");
+                        }
+                    }
+                }
+                "playing" => {
+                    let playing = value.get().unwrap();
+                    let distance = self.clock_distance();
+                    let was_playing = self.playing.replace(playing);
+
+                    self.restart_clock(distance);
+
+                    // the clock says where playback is
+                    let has_clock = self.clock.borrow().is_some();
+
+                    if playing && !was_playing {
+                        if has_clock {
+                            // catch up with the group after playing on its own
+                            self.sync_to_clock();
+                        } else if self.loop_count.get() >= 0 {
+                            self.rewind_if_ended();
+                        }
+                    }
+
+                    if playing {
+                        self.start_ticking();
+                    } else {
+                        self.stop_ticking();
+                    }
+
+                    obj.invalidate_contents();
+
+                    if was_playing != playing {
+                        obj.notify("playing");
+                    }
+                }
+                "progress" => {
+                    let progress: f64 = value.get().unwrap();
+                    let (first, last) = self.timeline().bounds();
+                    let frame_num = first + ((last - first) as f64 * progress) as usize;
+                    self.jump_to(frame_num);
+                }
+                "playback-mode" => {
+                    let playback_mode = value.get().unwrap();
+                    if playback_mode != self.playback_mode.get() {
+                        self.keep_frame(|| self.playback_mode.set(playback_mode));
+                        obj.notify("playback-mode");
+                    }
+                }
+                "reversed" => {
+                    let reversed = value.get().unwrap();
+                    if reversed != self.reversed.get() {
+                        self.keep_frame(|| self.reversed.set(reversed));
+                        obj.notify("reversed");
+                    }
+                }
+                "speed" => {
+                    let speed = value.get().unwrap();
+                    if speed != self.speed.get() {
+                        self.keep_frame(|| self.speed.set(speed));
+                        obj.notify("speed");
+                    }
+                }
+                "use-cache" => {
+                    let use_cache = value.get().unwrap();
+                    if use_cache != self.use_cache.replace(use_cache) {
+                        self.drop_cache();
+                        obj.notify("use-cache");
+                    }
+                }
+                _ => unimplemented!(),
+            }
+        }
+    }
+
+    impl PaintableImpl for AnimationPaintable {
+        fn snapshot(&self, snapshot: &gdk::Snapshot, width: f64, height: f64) {
+            self.resize(width, height);
+
+            if let Some(texture) = &*self.texture.borrow() {
+                texture.snapshot(snapshot, width, height);
+                self.undrawn_since.set(None);
+            }
+
+            // it stopped while nobody drew it
+            if self.playing.get() {
+                self.start_ticking();
+            }
+        }
+
+        fn current_image(&self) -> gdk::Paintable {
+            match &*self.texture.borrow() {
+                Some(texture) => texture.clone().upcast(),
+                None => {
+                    let (width, height) = self.default_size.get();
+                    gdk::Paintable::new_empty(width, height)
+                }
+            }
+        }
+
+        fn intrinsic_width(&self) -> i32 {
+            self.default_size.get().0
+        }
+
+        fn intrinsic_height(&self) -> i32 {
+            self.default_size.get().1
+        }
+
+        fn intrinsic_aspect_ratio(&self) -> f64 {
+            let (width, height) = self.default_size.get();
+
+            if height == 0 {
+                0.0
+            } else {
+                width as f64 / height as f64
+            }
+        }
+    }
+
+    impl AnimationPaintable {
+        /// Stops using the frame cache, the current frame stays on screen.
+        ///
+        /// Frames are freed when no other paintable uses them.
+        pub fn drop_cache(&self) {
+            self.frame_cache.take();
+        }
+
+        /// Returns the timeline of the current mode, direction and bounds.
+        pub(super) fn timeline(&self) -> Timeline {
+            Timeline {
+                totalframe: self.totalframe.get(),
+                segment: self.segment.get(),
+                playback_mode: self.playback_mode.get(),
+                reversed: self.reversed.get(),
+                speed: self.speed.get(),
+            }
+        }
+
+        /// Returns how many frames per second playback goes through.
+        pub(super) fn rate(&self) -> f64 {
+            self.framerate.get() * self.speed.get().abs()
+        }
+
+        /// Returns the time in microseconds, from the shared clock if there is one.
+        fn now(&self) -> i64 {
+            match &*self.clock.borrow() {
+                Some(clock) => clock.imp().time(),
+                None => glib::monotonic_time(),
+            }
+        }
+
+        fn disconnect_clock(&self) {
+            if let Some(clock) = &*self.clock.borrow() {
+                for handler in self.clock_handlers.take() {
+                    clock.disconnect(handler);
+                }
+            }
+        }
+
+        /// Starts following the clock, or the monotonic time without it.
+        fn attach_clock(&self, clock: Option<AnimationClock>) {
+            let obj = self.obj();
+            let distance = self.clock_distance();
+
+            self.disconnect_clock();
+            self.clock.replace(clock.clone());
+
+            let Some(clock) = clock else {
+                self.restart_clock(distance);
+                return;
+            };
+
+            let handlers = vec![
+                clock.connect_notify_local(
+                    Some("playing"),
+                    clone!(
+                        #[weak(rename_to = imp)]
+                        self,
+                        move |clock, _| imp.follow_clock_playing(clock)
+                    ),
+                ),
+                clock.connect_seeked(clone!(
+                    #[weak]
+                    obj,
+                    move |_| obj.imp().sync_to_clock()
+                )),
+            ];
+
+            self.clock_handlers.replace(handlers);
+
+            self.follow_clock_playing(&clock);
+            self.sync_to_clock();
+        }
+
+        /// Plays or pauses with the clock.
+        ///
+        /// It goes through the owner, so it may keep the animation paused.
+        fn follow_clock_playing(&self, clock: &AnimationClock) {
+            let target: glib::Object = match self.owner.upgrade() {
+                Some(widget) => widget.upcast(),
+                None => self.obj().clone().upcast(),
+            };
+
+            target.set_property("playing", clock.is_playing());
+        }
+
+        /// Moves to the frame the clock points to.
+        pub(super) fn sync_to_clock(&self) {
+            let Some(time) = self.clock.borrow().as_ref().map(AnimationClock::time) else {
+                return;
+            };
+
+            let distance = time.as_secs_f64() * self.rate();
+
+            self.restart_clock(distance);
+            self.obj().setup_frame(self.timeline().frame_at(distance));
+            self.set_loops(self.timeline().iteration(distance).max(0) as u32);
+        }
+
+        /// Returns how many frames playback went through, it only grows while playing.
+        pub(super) fn clock_distance(&self) -> f64 {
+            if !self.playing.get() {
+                let distance = self.start_distance.get();
+
+                // playback ended beyond the frame it stopped on
+                if self.timeline().frame_at(distance) != self.frame_num.get() {
+                    return self.timeline().distance_for(self.frame_num.get(), distance);
+                }
+
+                return distance;
+            }
+
+            let elapsed = (self.now() - self.clock_start.get()) as f64 / 1_000_000.0;
+            self.start_distance.get() + elapsed * self.rate()
+        }
+
+        /// Continues playback from the given distance.
+        pub(super) fn restart_clock(&self, distance: f64) {
+            self.clock_start.set(self.now());
+            self.start_distance.set(distance);
+            self.clock_iteration
+                .set(self.timeline().iteration(distance));
+        }
+
+        /// Applies a change of the direction, mode or bounds,
+        /// playback continues from the same frame.
+        pub(super) fn keep_frame(&self, change: impl FnOnce()) {
+            let distance = self.clock_distance();
+            let timeline = self.timeline();
+            let frame_num = timeline.frame_at(distance);
+
+            change();
+
+            let new_timeline = self.timeline();
+
+            if new_timeline.same_frames(&timeline) {
+                self.restart_clock(distance);
+            } else {
+                self.restart_clock(new_timeline.distance_for(frame_num, distance));
+            }
+        }
+
+        /// Shows the frame and continues playback from it.
+        pub(super) fn jump_to(&self, frame_num: usize) {
+            self.restart_clock(
+                self.timeline()
+                    .distance_for(frame_num, self.clock_distance()),
+            );
+            self.obj().setup_frame(frame_num);
+        }
+
+        /// Sets how many times playback started over.
+        pub(super) fn set_loops(&self, loops: u32) {
+            if self.loops.replace(loops) != loops {
+                self.obj().notify("iteration");
+            }
+        }
+
+        /// Sets the frame playback is at, it may be not on screen yet.
+        pub(super) fn set_frame_num(&self, frame_num: usize) {
+            if self.frame_num.replace(frame_num) != frame_num {
+                self.obj().notify("progress");
+            }
+        }
+
+        /// Starts over when playback stopped on the end frame.
+        fn rewind_if_ended(&self) {
+            if self.frame_num.get() == self.timeline().end_frame() {
+                self.rewind();
+            }
+        }
+
+        /// Moves to the start frame, like playback never happened.
+        pub(super) fn rewind(&self) {
+            self.restart_clock(0.0);
+            self.jump_to(self.timeline().start_frame());
+            self.set_loops(0);
+        }
+
+        /// Ticks with the frame clock of the owner widget, or a timer without it.
+        fn start_ticking(&self) {
+            if self.ticker.borrow().is_some() {
+                return;
+            }
+
+            let obj = self.obj();
+
+            let ticker = match self.owner.upgrade() {
+                Some(widget) => {
+                    let id = widget.add_tick_callback(clone!(
+                        #[weak]
+                        obj,
+                        #[upgrade_or]
+                        glib::ControlFlow::Break,
+                        move |_, _| obj.tick()
+                    ));
+
+                    Ticker::FrameClock(id)
+                }
+                None => Ticker::Timer(glib::timeout_add_local(
+                    TICK_INTERVAL,
+                    clone!(
+                        #[weak]
+                        obj,
+                        #[upgrade_or]
+                        glib::ControlFlow::Break,
+                        move || obj.tick()
+                    ),
+                )),
+            };
+
+            self.ticker.replace(Some(ticker));
+        }
+
+        fn stop_ticking(&self) {
+            if let Some(ticker) = self.ticker.take() {
+                ticker.remove();
+            }
+        }
+
+        /// Renders frames in the smallest size with the aspect ratio
+        /// of the animation that covers the drawn area, the texture is stretched to it.
+        fn resize(&self, width: f64, height: f64) {
+            let aspect_ratio = self.intrinsic_aspect_ratio();
+
+            let size = if aspect_ratio == 0.0 || height == 0.0 {
+                (width, height)
+            } else if width / height > aspect_ratio {
+                (width, width / aspect_ratio)
+            } else {
+                (height * aspect_ratio, height)
+            };
+
+            self.size.set(size);
+        }
+    }
+}
+
+glib::wrapper! {
+    /// Paintable that displays vector lottie animation
+    ///
+    /// Use it to show animations in [`gtk::Picture`], [`gtk::Image`]
+    /// or in your own snapshot code.
+    pub struct AnimationPaintable(ObjectSubclass<imp::AnimationPaintable>)
+        @implements gdk::Paintable;
+}
+
+impl Default for AnimationPaintable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnimationPaintable {
+    /// Creates empty paintable, use [`AnimationPaintable::open`] to load animation.
+    pub fn new() -> Self {
+        glib::Object::new()
+    }
+
+    fn tick(&self) -> glib::ControlFlow {
+        let imp = self.imp();
+
+        let totalframe = imp.totalframe.get();
+
+        if totalframe == 0 {
+            return glib::ControlFlow::Continue;
+        }
+
+        // nobody draws the paintable, so don't render new frames,
+        // ticking starts again once it's drawn
+        if imp
+            .undrawn_since
+            .get()
+            .is_some_and(|instant| instant.elapsed() > UNDRAWN_TIMEOUT)
+        {
+            imp.drop_cache();
+            // the source is removed by returning break
+            imp.ticker.take();
+            return glib::ControlFlow::Break;
+        }
+
+        let distance = imp.clock_distance();
+
+        let iteration = imp.timeline().iteration(distance);
+        let passed = iteration.saturating_sub(imp.clock_iteration.get()).max(0) as u64;
+
+        let loops_left = match u64::try_from(imp.loop_count.get()) {
+            Ok(loop_count) => loop_count.saturating_sub(imp.loops.get() as u64),
+            // loops forever
+            Err(_) => u64::MAX,
+        };
+
+        let ended = passed > loops_left;
+
+        let frame = if ended {
+            imp.timeline().end_frame()
+        } else {
+            imp.timeline().frame_at(distance)
+        };
+
+        if frame != imp.frame_num.get() {
+            self.setup_frame(frame);
+        }
+
+        if ended {
+            self.pause();
+            self.emit_by_name::<()>("ended", &[]);
+        } else if passed > 0 {
+            let loops = imp.loops.get().saturating_add(passed as u32);
+            imp.clock_iteration.set(iteration);
+            imp.set_loops(loops);
+            self.emit_by_name::<()>("looped", &[&loops]);
+        }
+
+        glib::ControlFlow::Continue
+    }
+
+    /// Makes the frame current and renders it.
+    fn setup_frame(&self, frame_num: usize) {
+        let imp = self.imp();
+
+        imp.set_frame_num(frame_num);
+
+        // not loaded yet, or failed to load
+        if imp.totalframe.get() == 0 {
+            return;
+        }
+
+        let Some(animation) = imp.animation.borrow().clone() else {
+            return;
+        };
+
+        let (width, height) = imp.size.get();
+        let scale_factor = imp.scale_factor.get() as f64;
+        let size = (
+            (width * scale_factor) as i32,
+            (height * scale_factor) as i32,
+        );
+
+        if size.0 <= 0 || size.1 <= 0 {
+            return;
+        }
+
+        if !imp.use_cache.get() {
+            self.render_frame(animation, frame_num, size);
+            return;
+        }
+
+        let frame_cache = {
+            let mut frame_cache = imp.frame_cache.borrow_mut();
+
+            match &*frame_cache {
+                Some(frame_cache) if frame_cache.size() == size => frame_cache.clone(),
+                _ => frame_cache.insert(animation.frame_cache(size)).clone(),
+            }
+        };
+
+        if let Some(texture) = frame_cache.cached_frame(frame_num) {
+            self.show_frame(&animation, frame_num, texture);
+        } else {
+            glib::spawn_future_local(clone!(
+                #[weak(rename_to = obj)]
+                self,
+                async move {
+                    match frame_cache.frame(frame_num).await {
+                        Some(texture) => obj.show_frame(&animation, frame_num, texture),
+                        None => obj.check_failed(&animation),
+                    }
+                }
+            ));
+        }
+    }
+
+    /// Renders the frame without the cache.
+    ///
+    /// Only one frame is rendered at a time, frames requested meanwhile
+    /// are skipped and the current one is rendered next.
+    fn render_frame(&self, animation: Rc<SharedAnimation>, frame_num: usize, size: (i32, i32)) {
+        let imp = self.imp();
+
+        if let Some(request) = &*imp.render_request.borrow() {
+            if Rc::ptr_eq(&request.animation, &animation) && request.size == size {
+                return;
+            }
+        }
+
+        // it's for another animation or size, nobody needs it
+        if let Some(request) = imp.render_request.take() {
+            request.handle.abort();
+        }
+
+        let handle = glib::spawn_future_local(clone!(
+            #[weak(rename_to = obj)]
+            self,
+            #[strong]
+            animation,
+            async move {
+                let texture = animation.render(frame_num, size).await;
+
+                let imp = obj.imp();
+                imp.render_request.take();
+
+                match texture {
+                    Some(texture) => obj.show_frame(&animation, frame_num, texture),
+                    None => obj.check_failed(&animation),
+                }
+
+                // moved to another frame while this one was rendering
+                if imp.frame_num.get() != frame_num {
+                    obj.setup_frame(imp.frame_num.get());
+                }
+            }
+        ));
+
+        imp.render_request.replace(Some(RenderRequest {
+            handle,
+            animation,
+            size,
+        }));
+    }
+
+    fn show_frame(
+        &self,
+        animation: &Rc<SharedAnimation>,
+        frame_num: usize,
+        texture: gdk::MemoryTexture,
+    ) {
+        let imp = self.imp();
+
+        // frame of the animation that was opened before
+        if !imp
+            .animation
+            .borrow()
+            .as_ref()
+            .is_some_and(|current| Rc::ptr_eq(current, animation))
+        {
+            return;
+        }
+
+        // it's late, while paused only the current frame may be shown
+        if !imp.playing.get() && frame_num != imp.frame_num.get() {
+            return;
+        }
+
+        imp.texture.replace(Some(texture));
+
+        if imp.undrawn_since.get().is_none() {
+            imp.undrawn_since.set(Some(std::time::Instant::now()));
+        }
+
+        self.invalidate_contents();
+
+        if imp.shown_frame.replace(Some(frame_num)) != Some(frame_num) {
+            self.emit_by_name::<()>("frame-changed", &[&(frame_num as u32)]);
+        }
+    }
+
+    /// Shows the frame, same as [`AnimationPaintable::set_frame`].
+    pub fn request_draw(&self, frame_num: usize) {
+        self.set_frame(frame_num);
+    }
+
+    /// Returns the current frame.
+    ///
+    /// It may still be rendering, see [`AnimationPaintable::displayed_frame`].
+    pub fn frame(&self) -> usize {
+        self.imp().frame_num.get()
+    }
+
+    /// Moves to the frame, playback continues from it.
+    ///
+    /// Works while paused too, `frame-changed` is emitted once the frame is on screen.
+    pub fn set_frame(&self, frame_num: usize) {
+        let imp = self.imp();
+        let (first, last) = imp.timeline().bounds();
+        imp.jump_to(frame_num.clamp(first, last));
+    }
+
+    /// Returns the frame that is on screen.
+    pub fn displayed_frame(&self) -> Option<usize> {
+        self.imp().shown_frame.get()
+    }
+
+    /// Moves to the next frame, it stops on the last one.
+    pub fn step_forward(&self) {
+        self.set_frame(self.frame().saturating_add(1));
+    }
+
+    /// Moves to the previous frame, it stops on the first one.
+    pub fn step_backward(&self) {
+        self.set_frame(self.frame().saturating_sub(1));
+    }
+
+    pub fn open(&self, file: gio::File) {
+        self.load(Source::File(file));
+    }
+
+    /// Opens json or tgs data.
+    ///
+    /// `cache_key` is used by rlottie to cache the parsed animation,
+    /// so different data must have different keys.
+    pub fn open_bytes(&self, data: &glib::Bytes, cache_key: &str) {
+        self.load(Source::Bytes {
+            data: data.clone(),
+            cache_key: cache_key.to_owned(),
+        });
+    }
+
+    /// Reads the stream to the end and opens its data as json or tgs.
+    ///
+    /// See [`AnimationPaintable::open_bytes`] for `cache_key`.
+    pub async fn load_from_stream(
+        &self,
+        stream: &impl IsA<gio::InputStream>,
+        cache_key: &str,
+    ) -> Result<(), Error> {
+        self.clear_error();
+
+        match loader::read_stream(stream.upcast_ref()).await {
+            Ok(data) => {
+                self.open_bytes(&data, cache_key);
+                Ok(())
+            }
+            Err(error) => {
+                self.fail_loading(error.clone());
+                Err(error)
+            }
+        }
+    }
+
+    fn fail_loading(&self, error: Error) {
+        self.imp().error.replace(Some(error.clone()));
+        self.notify("error");
+        self.emit_by_name::<()>("load-failed", &[&error]);
+    }
+
+    /// Reports the current animation as failed if rendering it crashed.
+    fn check_failed(&self, animation: &Rc<SharedAnimation>) {
+        let imp = self.imp();
+
+        if !imp
+            .animation
+            .borrow()
+            .as_ref()
+            .is_some_and(|current| Rc::ptr_eq(current, animation))
+        {
+            return;
+        }
+
+        let Some(error) = animation.error() else {
+            return;
+        };
+
+        self.pause();
+        imp.animation.take();
+        imp.frame_cache.take();
+        imp.totalframe.set(0);
+
+        self.notify_metadata();
+        self.fail_loading(error);
+    }
+
+    fn clear_error(&self) {
+        if self.imp().error.take().is_some() {
+            self.notify("error");
+        }
+    }
+
+    fn notify_metadata(&self) {
+        let _guard = self.freeze_notify();
+
+        for name in [
+            "default-height",
+            "default-width",
+            "duration",
+            "framerate",
+            "loaded",
+            "total-frames",
+        ] {
+            self.notify(name);
+        }
+    }
+
+    fn load(&self, source: Source) {
+        let imp = self.imp();
+
+        self.clear_error();
+        imp.frame_cache.take();
+        imp.texture.take();
+        imp.shown_frame.take();
+        imp.set_loops(0);
+        imp.totalframe.set(0);
+        imp.framerate.set(0.0);
+        imp.default_size.set((0, 0));
+        imp.markers.take();
+        imp.reloading.take();
+
+        if let Some(source) = imp.reload_source.take() {
+            source.remove();
+        }
+
+        self.notify_metadata();
+
+        let animation = SharedAnimation::for_source(source.clone(), &imp.overrides.borrow());
+        imp.source.replace(Some(source));
+
+        imp.animation.replace(Some(animation.clone()));
+
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let result = animation.info().await;
+
+                let imp = obj.imp();
+
+                // another animation was opened while this one was loading
+                if !imp
+                    .animation
+                    .borrow()
+                    .as_ref()
+                    .is_some_and(|current| Rc::ptr_eq(current, &animation))
+                {
+                    return;
+                }
+
+                match result {
+                    Ok(animation_info) => {
+                        let AnimationInfo {
+                            totalframe,
+                            default_size,
+                            framerate,
+                            frame_delay,
+                            markers,
+                        } = animation_info;
+
+                        imp.markers.replace(markers);
+
+                        imp.frame_delay.set(frame_delay);
+                        imp.framerate.set(framerate);
+                        imp.totalframe.set(totalframe);
+
+                        let (width, height) = default_size;
+                        imp.size.set((width as f64, height as f64));
+                        imp.default_size.set(default_size);
+
+                        obj.invalidate_size();
+
+                        // the clock may have been running while the animation loaded
+                        if imp.clock.borrow().is_some() {
+                            imp.sync_to_clock();
+                        } else {
+                            imp.restart_clock(0.0);
+                            imp.jump_to(imp.timeline().start_frame());
+                        }
+
+                        obj.notify_metadata();
+                        obj.emit_by_name::<()>("loaded", &[]);
+                    }
+                    Err(error) => obj.fail_loading(error),
+                }
+            }
+        ));
+    }
+
+    /// Opens the animation again with the current overrides.
+    ///
+    /// Frames of the old one are shown until it's loaded.
+    fn reload(&self) {
+        let imp = self.imp();
+
+        let Some(source) = imp.source.borrow().clone() else {
+            return;
+        };
+
+        // nothing is shown yet
+        if imp.totalframe.get() == 0 {
+            self.load(source);
+            return;
+        }
+
+        let animation = SharedAnimation::for_source(source, &imp.overrides.borrow());
+
+        imp.reloading.replace(Some(animation.clone()));
+
+        glib::spawn_future_local(clone!(
+            #[weak(rename_to = obj)]
+            self,
+            async move {
+                let result = animation.info().await;
+
+                let imp = obj.imp();
+
+                // overrides changed again, or another animation was opened
+                if !imp
+                    .reloading
+                    .borrow()
+                    .as_ref()
+                    .is_some_and(|current| Rc::ptr_eq(current, &animation))
+                {
+                    return;
+                }
+
+                imp.reloading.take();
+
+                if result.is_ok() {
+                    imp.animation.replace(Some(animation));
+                    imp.frame_cache.take();
+                    obj.setup_frame(imp.frame_num.get());
+                }
+            }
+        ));
+    }
+
+    fn change_overrides(&self, keypath: &str, f: impl FnOnce(&mut Overrides)) {
+        if keypath.contains('\0') {
+            glib::g_critical!("gtk-rlottie", "keypath must not contain nul bytes");
+            return;
+        }
+
+        self.update_overrides(f);
+    }
+
+    fn update_overrides(&self, f: impl FnOnce(&mut Overrides)) {
+        let changed = {
+            let mut overrides = self.imp().overrides.borrow_mut();
+            let old = overrides.clone();
+            f(&mut overrides);
+            *overrides != old
+        };
+
+        let imp = self.imp();
+
+        if changed && imp.reload_source.borrow().is_none() {
+            let source = glib::idle_add_local_once(clone!(
+                #[weak(rename_to = obj)]
+                self,
+                move || {
+                    obj.imp().reload_source.take();
+                    obj.reload();
+                }
+            ));
+
+            imp.reload_source.replace(Some(source));
+        }
+    }
+
+    /// Sets the widget showing the paintable.
+    ///
+    /// Playback follows its frame clock, and the clock plays and pauses through it.
+    pub(crate) fn set_owner(&self, owner: &impl IsA<gtk::Widget>) {
+        self.imp().owner.set(Some(owner.upcast_ref()));
+    }
+
+    /// Sets the color of all fills and strokes, keypath overrides are applied over it.
+    pub(crate) fn set_tint(&self, color: Option<&gdk::RGBA>) {
+        self.update_overrides(|overrides| overrides.set_tint(color));
+    }
+
+    /// Creates animation from json of tgs files.
+    pub fn from_file(file: &impl IsA<gio::File>) -> Self {
+        let obj = Self::new();
+        obj.open(file.to_owned().upcast());
+        obj
+    }
+
+    /// Creates animation from json of tgs files from the given filename.
+    pub fn from_filename(path: &str) -> Self {
+        let file = gio::File::for_path(path);
+        Self::from_file(&file)
+    }
+
+    /// Creates animation from json or tgs file in the resource bundle.
+    pub fn from_resource(path: &str) -> Self {
+        let file = gio::File::for_uri(&format!("resource://{path}"));
+        Self::from_file(&file)
+    }
+
+    /// Creates animation from json or tgs data.
+    ///
+    /// See [`AnimationPaintable::open_bytes`] for `cache_key`.
+    pub fn from_bytes(data: &glib::Bytes, cache_key: &str) -> Self {
+        let obj = Self::new();
+        obj.open_bytes(data, cache_key);
+        obj
+    }
+
+    /// Returns the error that occurred while loading the animation, if any.
+    pub fn error(&self) -> Option<Error> {
+        self.property("error")
+    }
+
+    /// Connects to the signal emitted when the animation could not be loaded.
+    pub fn connect_load_failed<F: Fn(&Self, &Error) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "load-failed",
+            false,
+            glib::closure_local!(move |obj: &Self, error: &Error| f(obj, error)),
+        )
+    }
+
+    /// Connects to the signal emitted when the animation is loaded.
+    pub fn connect_loaded<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "loaded",
+            false,
+            glib::closure_local!(move |obj: &Self| f(obj)),
+        )
+    }
+
+    /// Connects to the signal emitted when a new frame is shown.
+    pub fn connect_frame_changed<F: Fn(&Self, u32) + 'static>(
+        &self,
+        f: F,
+    ) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "frame-changed",
+            false,
+            glib::closure_local!(move |obj: &Self, frame: u32| f(obj, frame)),
+        )
+    }
+
+    /// Connects to the signal emitted when looping playback starts over.
+    ///
+    /// The callback gets how many times the animation looped.
+    pub fn connect_looped<F: Fn(&Self, u32) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "looped",
+            false,
+            glib::closure_local!(move |obj: &Self, count: u32| f(obj, count)),
+        )
+    }
+
+    /// Connects to the signal emitted when playback stops on the last frame.
+    pub fn connect_ended<F: Fn(&Self) + 'static>(&self, f: F) -> glib::SignalHandlerId {
+        self.connect_closure(
+            "ended",
+            false,
+            glib::closure_local!(move |obj: &Self| f(obj)),
+        )
+    }
+
+    /// Returns the scale factor frames are rendered with.
+    pub fn scale_factor(&self) -> i32 {
+        self.imp().scale_factor.get()
+    }
+
+    /// Sets the scale factor frames are rendered with.
+    ///
+    /// [`crate::Animation`] keeps it in sync with the widget,
+    /// set it yourself when drawing the paintable on HiDPI displays.
+    pub fn set_scale_factor(&self, scale_factor: i32) {
+        self.imp().scale_factor.set(scale_factor.max(1));
+    }
+
+    /// Returns whether the animation is loaded.
+    pub fn is_loaded(&self) -> bool {
+        self.property("loaded")
+    }
+
+    /// Returns how many frames the animation has.
+    ///
+    /// It's zero until the animation is loaded.
+    pub fn total_frames(&self) -> usize {
+        self.imp().totalframe.get()
+    }
+
+    /// Returns frames per second of the animation.
+    ///
+    /// It's zero until the animation is loaded.
+    pub fn framerate(&self) -> f64 {
+        self.imp().framerate.get()
+    }
+
+    /// Returns the duration of the animation.
+    ///
+    /// It's zero until the animation is loaded.
+    pub fn duration(&self) -> Duration {
+        let imp = self.imp();
+        imp.frame_delay.get() * imp.totalframe.get() as u32
+    }
+
+    /// Returns whether the animation caches rendered frames.
+    pub fn use_cache(&self) -> bool {
+        self.property("use-cache")
+    }
+
+    /// Set to use the cache or not.
+    ///
+    /// Cache uses ram to reduse cpu usage,
+    /// you can disable it when animation
+    /// plays once and don't need a cache
+    pub fn set_use_cache(&self, value: bool) {
+        self.set_property("use-cache", value);
+    }
+
+    /// Reversed frame order.
+    pub fn is_reversed(&self) -> bool {
+        self.property("reversed")
+    }
+
+    /// Sets reversed or default frame order.
+    pub fn set_reversed(&self, value: bool) {
+        self.set_property("reversed", value);
+    }
+
+    /// Moves to the frame playback starts from.
+    pub(crate) fn rewind(&self) {
+        self.imp().rewind();
+    }
+
+    /// Returns the clock the animation follows.
+    pub fn clock(&self) -> Option<AnimationClock> {
+        self.property("clock")
+    }
+
+    /// Makes the animation follow the shared clock, or its own one with `None`.
+    ///
+    /// Animations of the same clock compute frames from the same time,
+    /// play, pause and seek the clock to drive them together.
+    pub fn set_clock(&self, clock: Option<&AnimationClock>) {
+        self.set_property("clock", clock);
+    }
+
+    /// Returns the order frames are played in.
+    pub fn playback_mode(&self) -> PlaybackMode {
+        self.property("playback-mode")
+    }
+
+    /// Sets the order frames are played in.
+    ///
+    /// [`AnimationPaintable::set_reversed`] and negative speed flip the direction of any mode.
+    pub fn set_playback_mode(&self, playback_mode: PlaybackMode) {
+        self.set_property("playback-mode", playback_mode);
+    }
+
+    /// Returns the playback speed.
+    pub fn speed(&self) -> f64 {
+        self.property("speed")
+    }
+
+    /// Sets the playback speed, `1.0` is the normal speed.
+    ///
+    /// Negative speed plays frames backwards,
+    /// and [`AnimationPaintable::is_reversed`] flips the direction once more.
+    pub fn set_speed(&self, speed: f64) {
+        self.set_property("speed", speed);
+    }
+
+    /// Limits playback to frames from `start` up to, but not including, `end`.
+    ///
+    /// Looping and [`AnimationPaintable::progress`] are bounded to the segment too.
+    pub fn set_segment(&self, start: usize, end: usize) {
+        let imp = self.imp();
+
+        imp.keep_frame(|| {
+            imp.segment
+                .set(Some((start, end.max(start.saturating_add(1)))))
+        });
+
+        let (first, last) = imp.timeline().bounds();
+
+        if !(first..=last).contains(&imp.frame_num.get()) {
+            imp.jump_to(imp.timeline().start_frame());
+        }
+
+        self.notify("progress");
+    }
+
+    /// Plays the segment from its start, see [`AnimationPaintable::set_segment`].
+    pub fn play_segment(&self, start: usize, end: usize, loop_: bool) {
+        self.set_segment(start, end);
+        self.set_loop(loop_);
+
+        self.imp().rewind();
+        self.play();
+    }
+
+    /// Returns the segment playback is limited to.
+    pub fn segment(&self) -> Option<(usize, usize)> {
+        self.imp().segment.get()
+    }
+
+    /// Plays the whole animation again.
+    pub fn clear_segment(&self) {
+        let imp = self.imp();
+        imp.keep_frame(|| imp.segment.set(None));
+        self.notify("progress");
+    }
+
+    /// Sets the fill color of the layers matching the keypath.
+    ///
+    /// Keypaths are layer names separated by dots, `*` matches
+    /// one name and `**` any number of them, e.g. `"**.Fill 1"`.
+    /// Alpha is ignored, see [`AnimationPaintable::set_opacity`].
+    ///
+    /// Overrides apply to the next opened animations too.
+    /// Overrides changed together are applied at once, on idle,
+    /// and frames are rendered again, they aren't shared
+    /// with animations that have other overrides.
+    pub fn set_fill_color(&self, keypath: &str, color: &gdk::RGBA) {
+        self.change_overrides(keypath, |overrides| {
+            overrides.set_color(keypath, Property::FillColor, color)
+        });
+    }
+
+    /// Sets the stroke color of the layers matching the keypath.
+    ///
+    /// See [`AnimationPaintable::set_fill_color`].
+    pub fn set_stroke_color(&self, keypath: &str, color: &gdk::RGBA) {
+        self.change_overrides(keypath, |overrides| {
+            overrides.set_color(keypath, Property::StrokeColor, color)
+        });
+    }
+
+    /// Sets the opacity of fills and strokes matching the keypath, from 0 to 1.
+    ///
+    /// See [`AnimationPaintable::set_fill_color`].
+    pub fn set_opacity(&self, keypath: &str, opacity: f64) {
+        self.change_overrides(keypath, |overrides| overrides.set_opacity(keypath, opacity));
+    }
+
+    /// Sets the stroke width of the layers matching the keypath.
+    ///
+    /// See [`AnimationPaintable::set_fill_color`].
+    pub fn set_stroke_width(&self, keypath: &str, width: f64) {
+        self.change_overrides(keypath, |overrides| {
+            overrides.set_stroke_width(keypath, width)
+        });
+    }
+
+    /// Removes overrides set for the keypath.
+    pub fn clear_override(&self, keypath: &str) {
+        self.change_overrides(keypath, |overrides| overrides.clear(keypath));
+    }
+
+    /// Returns markers of the animation.
+    ///
+    /// It's empty until the animation is loaded.
+    pub fn markers(&self) -> Vec<Marker> {
+        self.imp().markers.borrow().clone()
+    }
+
+    fn marker(&self, name: &str) -> Option<Marker> {
+        self.imp()
+            .markers
+            .borrow()
+            .iter()
+            .find(|marker| marker.name == name)
+            .cloned()
+    }
+
+    /// Plays the part of the animation under the marker.
+    ///
+    /// It loops if [`AnimationPaintable::is_loop`] is set.
+    /// Returns `false` if there is no such marker.
+    pub fn play_marker(&self, name: &str) -> bool {
+        let Some(marker) = self.marker(name) else {
+            return false;
+        };
+
+        self.play_segment(marker.start, marker.end, self.is_loop());
+        true
+    }
+
+    /// Moves to the first frame of the marker, playback isn't limited to it.
+    ///
+    /// The segment is cleared, so the marker is reachable wherever it is.
+    /// Returns `false` if there is no such marker.
+    pub fn seek_marker(&self, name: &str) -> bool {
+        let Some(marker) = self.marker(name) else {
+            return false;
+        };
+
+        self.clear_segment();
+        self.set_frame(marker.start);
+        true
+    }
+
+    /// Moves to the frame shown at the time from the start of the animation.
+    ///
+    /// The frame is clamped to the segment, if there is one.
+    ///
+    /// Playback continues from there, in the current direction.
+    pub fn seek(&self, time: Duration) {
+        let imp = self.imp();
+        let (first, last) = imp.timeline().bounds();
+        let frame_num = (time.as_secs_f64() * imp.framerate.get()) as usize;
+
+        imp.jump_to(frame_num.clamp(first, last));
+    }
+
+    /// Returns the time of the current frame from the start of the animation.
+    pub fn position(&self) -> Duration {
+        let imp = self.imp();
+        imp.frame_delay.get() * imp.frame_num.get() as u32
+    }
+
+    /// Returns current progress.
+    pub fn progress(&self) -> f64 {
+        self.property("progress")
+    }
+
+    /// Sets current progress.
+    pub fn set_progress(&self, value: f64) {
+        self.set_property("progress", value);
+    }
+
+    // Media functions
+
+    /// Return whether the animation is currently playing.
+    pub fn is_playing(&self) -> bool {
+        self.imp().playing.get()
+    }
+
+    /// Play the animation.
+    pub fn play(&self) {
+        self.set_property("playing", true);
+    }
+
+    /// Pause the animation.
+    pub fn pause(&self) {
+        self.set_property("playing", false);
+    }
+
+    /// Returns whether the animation is set to loop.
+    pub fn is_loop(&self) -> bool {
+        self.property("loop")
+    }
+
+    /// Sets whether the animation should loop.
+    ///
+    /// Looping forever is the same as [`AnimationPaintable::set_loop_count`] with `-1`.
+    pub fn set_loop(&self, loop_: bool) {
+        self.set_property("loop", loop_);
+    }
+
+    /// Returns how many times the animation repeats.
+    pub fn loop_count(&self) -> i32 {
+        self.property("loop-count")
+    }
+
+    /// Sets how many times the animation repeats after playing once.
+    ///
+    /// `0` plays it once and `-1` loops forever.
+    pub fn set_loop_count(&self, loop_count: i32) {
+        self.set_property("loop-count", loop_count.max(-1));
+    }
+
+    /// Returns how many times the animation started over since playback started.
+    pub fn iteration(&self) -> u32 {
+        self.property("iteration")
+    }
+}
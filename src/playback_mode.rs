@@ -0,0 +1,16 @@
+use gtk::glib;
+
+/// Order the frames are played in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LottiePlaybackMode")]
+pub enum PlaybackMode {
+    /// From the first frame to the last one.
+    #[default]
+    Forward,
+    /// From the last frame to the first one.
+    Reverse,
+    /// From the first frame to the last one and back.
+    Bounce,
+    /// From the last frame to the first one and back.
+    ReverseBounce,
+}
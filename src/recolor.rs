@@ -0,0 +1,36 @@
+use gtk::glib;
+
+/// Which css color tints the animation, like symbolic icons.
+///
+/// All fills and strokes get the color, overrides set
+/// with [`crate::Animation::set_fill_color`] and others apply over it.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, glib::Enum)]
+#[enum_type(name = "LottieRecolor")]
+pub enum Recolor {
+    /// The animation keeps its colors.
+    #[default]
+    None,
+    /// The `color` of the widget.
+    Foreground,
+    /// The `accent_color` named color.
+    Accent,
+    /// The `success_color` named color.
+    Success,
+    /// The `warning_color` named color.
+    Warning,
+    /// The `error_color` named color.
+    Error,
+}
+
+impl Recolor {
+    /// Returns the named color, falls back to the widget color if the theme doesn't define it.
+    pub(crate) fn color_name(self) -> Option<&'static str> {
+        match self {
+            Self::None | Self::Foreground => None,
+            Self::Accent => Some("accent_color"),
+            Self::Success => Some("success_color"),
+            Self::Warning => Some("warning_color"),
+            Self::Error => Some("error_color"),
+        }
+    }
+}
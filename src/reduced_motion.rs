@@ -0,0 +1,16 @@
+use gtk::glib;
+
+/// What the widget shows when animations are disabled in [`gtk::Settings`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, glib::Boxed)]
+#[boxed_type(name = "LottieReducedMotionPolicy")]
+pub enum ReducedMotionPolicy {
+    /// Play anyway.
+    Ignore,
+    /// Stop on the first frame.
+    #[default]
+    ShowFirstFrame,
+    /// Stop on the last frame.
+    ShowLastFrame,
+    /// Stop on the first frame of the marker.
+    ShowMarker(String),
+}
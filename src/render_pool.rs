@@ -0,0 +1,323 @@
+use std::collections::VecDeque;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::Condvar;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use gtk::gdk;
+use gtk::glib;
+
+use crate::loader;
+use crate::loader::Source;
+use crate::overrides::Overrides;
+use crate::Error;
+use crate::Marker;
+
+/// Sets how many threads render animations.
+///
+/// Defaults to the number of cpus. Animations don't own threads,
+/// each of them is rendered by whichever thread is free.
+pub fn set_render_threads(threads: usize) {
+    let pool = pool();
+    pool.state.lock().unwrap().max_threads = threads.max(1);
+    pool.condvar.notify_all();
+}
+
+/// Returns how many threads render animations.
+pub fn render_threads() -> usize {
+    pool().state.lock().unwrap().max_threads
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct AnimationInfo {
+    pub(crate) totalframe: usize,
+    pub(crate) default_size: (i32, i32),
+    pub(crate) framerate: f64,
+    pub(crate) frame_delay: Duration,
+    pub(crate) markers: Vec<Marker>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RenderInfo {
+    pub(crate) frame_num: usize,
+    pub(crate) width: i32,
+    pub(crate) height: i32,
+    pub(crate) sender: async_channel::Sender<(usize, gdk::MemoryTexture)>,
+    /// Set when nobody waits for the frame anymore.
+    pub(crate) cancelled: Arc<AtomicBool>,
+}
+
+impl RenderInfo {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.sender.is_closed()
+    }
+}
+
+#[derive(Debug)]
+enum Job {
+    Load(
+        Source,
+        Overrides,
+        async_channel::Sender<Result<AnimationInfo, Error>>,
+    ),
+    Render(RenderInfo),
+}
+
+struct SendAnimation(rlottie::Animation);
+
+// SAFETY: rlottie animation isn't bound to a thread,
+// the pool makes sure only one thread uses it at a time.
+unsafe impl Send for SendAnimation {}
+
+#[derive(Default)]
+struct TaskState {
+    animation: Option<SendAnimation>,
+    jobs: VecDeque<Job>,
+    scheduled: bool,
+}
+
+/// Job queue of a single animation.
+#[derive(Default)]
+struct Task {
+    state: Mutex<TaskState>,
+    closed: AtomicBool,
+    /// A job panicked, the animation is gone.
+    failed: AtomicBool,
+}
+
+struct PoolState {
+    tasks: VecDeque<Arc<Task>>,
+    threads: usize,
+    idle_threads: usize,
+    max_threads: usize,
+}
+
+struct Pool {
+    state: Mutex<PoolState>,
+    condvar: Condvar,
+}
+
+fn pool() -> &'static Pool {
+    static POOL: OnceLock<Pool> = OnceLock::new();
+    POOL.get_or_init(|| Pool {
+        state: Mutex::new(PoolState {
+            tasks: VecDeque::new(),
+            threads: 0,
+            idle_threads: 0,
+            max_threads: std::thread::available_parallelism().map_or(1, |n| n.get()),
+        }),
+        condvar: Condvar::new(),
+    })
+}
+
+impl Pool {
+    fn schedule(&'static self, task: Arc<Task>) {
+        let mut state = self.state.lock().unwrap();
+
+        state.tasks.push_back(task);
+
+        if state.idle_threads == 0 && state.threads < state.max_threads {
+            state.threads += 1;
+            std::thread::spawn(move || self.work());
+        } else {
+            self.condvar.notify_one();
+        }
+    }
+
+    fn work(&'static self) {
+        loop {
+            let task = {
+                let mut state = self.state.lock().unwrap();
+
+                loop {
+                    if state.threads > state.max_threads {
+                        state.threads -= 1;
+                        return;
+                    }
+
+                    if let Some(task) = state.tasks.pop_front() {
+                        break task;
+                    }
+
+                    state.idle_threads += 1;
+                    state = self.condvar.wait(state).unwrap();
+                    state.idle_threads -= 1;
+                }
+            };
+
+            if task.run_job() {
+                self.schedule(task);
+            }
+        }
+    }
+}
+
+impl Task {
+    /// Runs the next job, returns whether there are more jobs.
+    fn run_job(&self) -> bool {
+        let (job, animation) = {
+            let mut state = self.state.lock().unwrap();
+
+            if self.closed.load(Ordering::Relaxed) {
+                state.jobs.clear();
+                state.animation = None;
+                state.scheduled = false;
+                return false;
+            }
+
+            match state.jobs.pop_front() {
+                Some(job) => (job, state.animation.take()),
+                None => {
+                    state.scheduled = false;
+                    return false;
+                }
+            }
+        };
+
+        // a panicking job must not take the worker or the queue with it,
+        // the animation is dropped then and following jobs do nothing
+        let result = std::panic::catch_unwind(AssertUnwindSafe(|| match job {
+            Job::Load(source, overrides, sender) => match loader::load_animation(source) {
+                // rlottie accepts files with any framerate, but the clock can't use them
+                Ok((animation, _))
+                    if !(animation.framerate().is_finite() && animation.framerate() > 0.0) =>
+                {
+                    _ = sender.send_blocking(Err(Error::Rejected));
+                    None
+                }
+                Ok((mut animation, markers)) => {
+                    overrides.apply(&mut animation);
+
+                    let size = animation.size();
+                    let framerate = animation.framerate();
+
+                    let animation_info = AnimationInfo {
+                        framerate,
+                        frame_delay: Duration::from_secs_f64(1.0 / framerate),
+                        totalframe: animation.totalframe(),
+                        markers,
+                        default_size: (size.width as i32, size.height as i32),
+                    };
+
+                    _ = sender.send_blocking(Ok(animation_info));
+
+                    Some(SendAnimation(animation))
+                }
+                Err(error) => {
+                    _ = sender.send_blocking(Err(error));
+                    None
+                }
+            },
+            Job::Render(render_info) => {
+                let mut animation = animation;
+                if let Some(SendAnimation(animation)) = &mut animation {
+                    if !render_info.is_cancelled() {
+                        render(animation, render_info);
+                    }
+                }
+                animation
+            }
+        }));
+
+        let animation = result.unwrap_or_else(|_| {
+            self.failed.store(true, Ordering::Relaxed);
+            None
+        });
+
+        let mut state = self.state.lock().unwrap();
+        state.animation = animation;
+
+        if state.jobs.is_empty() {
+            state.scheduled = false;
+            false
+        } else {
+            true
+        }
+    }
+}
+
+fn render(animation: &mut rlottie::Animation, render_info: RenderInfo) {
+    let RenderInfo {
+        frame_num,
+        width,
+        height,
+        sender,
+        ..
+    } = render_info;
+
+    let size = rlottie::Size::new(width as usize, height as usize);
+    let mut surface = rlottie::Surface::new(size);
+
+    animation.render(frame_num, &mut surface);
+
+    let data = glib::Bytes::from_owned(surface);
+
+    let texture = gdk::MemoryTexture::new(
+        width,
+        height,
+        gdk::MemoryFormat::B8g8r8a8,
+        &data,
+        width as usize * 4,
+    );
+
+    _ = sender.send_blocking((frame_num, texture));
+}
+
+/// Handle to the job queue of an animation.
+///
+/// Jobs run in order on the shared thread pool,
+/// pending jobs are dropped with the handle.
+#[derive(Default)]
+pub(crate) struct Renderer(Arc<Task>);
+
+impl std::fmt::Debug for Renderer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Renderer").finish_non_exhaustive()
+    }
+}
+
+impl Renderer {
+    pub(crate) fn load(
+        &self,
+        source: Source,
+        overrides: Overrides,
+        sender: async_channel::Sender<Result<AnimationInfo, Error>>,
+    ) {
+        self.push(Job::Load(source, overrides, sender));
+    }
+
+    pub(crate) fn render(&self, render_info: RenderInfo) {
+        self.push(Job::Render(render_info));
+    }
+
+    /// Returns whether a job panicked, frames aren't rendered then.
+    pub(crate) fn is_failed(&self) -> bool {
+        self.0.failed.load(Ordering::Relaxed)
+    }
+
+    fn push(&self, job: Job) {
+        let mut state = self.0.state.lock().unwrap();
+
+        // frames nobody waits for anymore don't pile up
+        state
+            .jobs
+            .retain(|job| !matches!(job, Job::Render(render_info) if render_info.is_cancelled()));
+        state.jobs.push_back(job);
+
+        if !state.scheduled {
+            state.scheduled = true;
+            drop(state);
+            pool().schedule(self.0.clone());
+        }
+    }
+}
+
+impl Drop for Renderer {
+    fn drop(&mut self) {
+        self.0.closed.store(true, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,332 @@
+use crate::PlaybackMode;
+
+/// Maps the distance playback went through to frames.
+///
+/// Distance is counted in frames from the start of playback and only grows,
+/// the mode, direction and bounds decide which frame it shows.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Timeline {
+    pub(crate) totalframe: usize,
+    pub(crate) segment: Option<(usize, usize)>,
+    pub(crate) playback_mode: PlaybackMode,
+    pub(crate) reversed: bool,
+    pub(crate) speed: f64,
+}
+
+impl Timeline {
+    /// Returns whether frames go from the last one to the first one.
+    pub(crate) fn is_backward(&self) -> bool {
+        let backward_mode = matches!(
+            self.playback_mode,
+            PlaybackMode::Reverse | PlaybackMode::ReverseBounce
+        );
+
+        backward_mode ^ self.reversed ^ (self.speed < 0.0)
+    }
+
+    pub(crate) fn is_bounce(&self) -> bool {
+        matches!(
+            self.playback_mode,
+            PlaybackMode::Bounce | PlaybackMode::ReverseBounce
+        )
+    }
+
+    /// Returns whether both timelines show the same frames at the same distances.
+    pub(crate) fn same_frames(&self, other: &Self) -> bool {
+        (self.is_backward(), self.is_bounce(), self.bounds())
+            == (other.is_backward(), other.is_bounce(), other.bounds())
+    }
+
+    /// Returns the first and the last frame playback goes through.
+    ///
+    /// They're the whole animation unless a segment is set.
+    pub(crate) fn bounds(&self) -> (usize, usize) {
+        let last_frame = self.totalframe.saturating_sub(1);
+
+        match self.segment {
+            Some((start, end)) => {
+                let first = start.min(last_frame);
+                (first, end.saturating_sub(1).clamp(first, last_frame))
+            }
+            None => (0, last_frame),
+        }
+    }
+
+    /// Returns how many frames a single iteration goes through.
+    ///
+    /// Bouncing iteration goes there and back.
+    fn period(&self) -> f64 {
+        let (first, last) = self.bounds();
+
+        if self.is_bounce() && last > first {
+            2.0 * (last - first) as f64
+        } else {
+            (last - first + 1) as f64
+        }
+    }
+
+    /// Returns how many iterations playback finished to reach the distance.
+    pub(crate) fn iteration(&self, distance: f64) -> i64 {
+        (distance / self.period()).floor() as i64
+    }
+
+    /// Returns the frame shown at the distance.
+    pub(crate) fn frame_at(&self, distance: f64) -> usize {
+        let (first, last) = self.bounds();
+        let span = (last - first) as f64;
+
+        let offset = if self.is_bounce() && span > 0.0 {
+            (span - (distance.rem_euclid(2.0 * span) - span).abs()).round()
+        } else {
+            distance.rem_euclid(span + 1.0).floor()
+        };
+
+        let offset = (offset as usize).min(last - first);
+
+        if self.is_backward() {
+            last - offset
+        } else {
+            first + offset
+        }
+    }
+
+    /// Returns the distance at which the frame is shown,
+    /// in the same iteration as the `near` distance.
+    pub(crate) fn distance_for(&self, frame_num: usize, near: f64) -> f64 {
+        let (first, last) = self.bounds();
+        let frame_num = frame_num.clamp(first, last);
+
+        let offset = if self.is_backward() {
+            last - frame_num
+        } else {
+            frame_num - first
+        } as f64;
+
+        let period = self.period();
+        let iteration_start = self.iteration(near) as f64 * period;
+
+        // bouncing back
+        if self.is_bounce() && near - iteration_start > period / 2.0 {
+            iteration_start + period - offset
+        } else {
+            iteration_start + offset
+        }
+    }
+
+    /// Returns the frame playback starts from.
+    pub(crate) fn start_frame(&self) -> usize {
+        self.frame_at(0.0)
+    }
+
+    /// Returns the frame playback stops on once iterations are over.
+    pub(crate) fn end_frame(&self) -> usize {
+        let (first, last) = self.bounds();
+
+        if self.is_bounce() != self.is_backward() {
+            first
+        } else {
+            last
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timeline(playback_mode: PlaybackMode) -> Timeline {
+        Timeline {
+            totalframe: 10,
+            segment: None,
+            playback_mode,
+            reversed: false,
+            speed: 1.0,
+        }
+    }
+
+    fn frames(timeline: &Timeline, distances: std::ops::Range<usize>) -> Vec<usize> {
+        distances
+            .map(|distance| timeline.frame_at(distance as f64))
+            .collect()
+    }
+
+    #[test]
+    fn forward_wraps() {
+        let timeline = timeline(PlaybackMode::Forward);
+
+        assert_eq!(frames(&timeline, 8..12), [8, 9, 0, 1]);
+        assert_eq!(timeline.iteration(9.9), 0);
+        assert_eq!(timeline.iteration(10.0), 1);
+        assert_eq!(timeline.frame_at(9.99), 9);
+    }
+
+    #[test]
+    fn reverse_wraps() {
+        let timeline = timeline(PlaybackMode::Reverse);
+
+        assert_eq!(timeline.start_frame(), 9);
+        assert_eq!(frames(&timeline, 8..12), [1, 0, 9, 8]);
+    }
+
+    #[test]
+    fn bounce_turnaround() {
+        let timeline = timeline(PlaybackMode::Bounce);
+
+        // the last frame is shown once, not twice
+        assert_eq!(frames(&timeline, 7..12), [7, 8, 9, 8, 7]);
+        assert_eq!(frames(&timeline, 17..21), [1, 0, 1, 2]);
+        assert_eq!(timeline.iteration(17.9), 0);
+        assert_eq!(timeline.iteration(18.0), 1);
+    }
+
+    #[test]
+    fn reverse_bounce_turnaround() {
+        let timeline = timeline(PlaybackMode::ReverseBounce);
+
+        assert_eq!(timeline.start_frame(), 9);
+        assert_eq!(frames(&timeline, 8..11), [1, 0, 1]);
+        assert_eq!(frames(&timeline, 17..20), [8, 9, 8]);
+    }
+
+    #[test]
+    fn negative_speed_and_reversed() {
+        let mut timeline = timeline(PlaybackMode::Forward);
+
+        timeline.speed = -0.5;
+        assert!(timeline.is_backward());
+        assert_eq!(frames(&timeline, 0..3), [9, 8, 7]);
+
+        // reversed flips the direction once more
+        timeline.reversed = true;
+        assert!(!timeline.is_backward());
+        assert_eq!(frames(&timeline, 0..3), [0, 1, 2]);
+
+        timeline.playback_mode = PlaybackMode::Reverse;
+        assert!(timeline.is_backward());
+    }
+
+    #[test]
+    fn segment_bounds() {
+        let mut timeline = timeline(PlaybackMode::Forward);
+
+        timeline.segment = Some((3, 6));
+        assert_eq!(timeline.bounds(), (3, 5));
+        assert_eq!(frames(&timeline, 0..4), [3, 4, 5, 3]);
+
+        // segments beyond the animation are clamped to it
+        timeline.segment = Some((7, 100));
+        assert_eq!(timeline.bounds(), (7, 9));
+        timeline.segment = Some((100, 200));
+        assert_eq!(timeline.bounds(), (9, 9));
+    }
+
+    #[test]
+    fn single_frame_segment() {
+        for playback_mode in [
+            PlaybackMode::Forward,
+            PlaybackMode::Reverse,
+            PlaybackMode::Bounce,
+            PlaybackMode::ReverseBounce,
+        ] {
+            let mut timeline = timeline(playback_mode);
+            timeline.segment = Some((4, 5));
+
+            assert_eq!(frames(&timeline, 0..3), [4, 4, 4]);
+            assert_eq!(timeline.iteration(0.0), 0);
+            assert_eq!(timeline.iteration(1.0), 1);
+            assert_eq!(timeline.iteration(2.5), 2);
+            assert_eq!(timeline.start_frame(), 4);
+            assert_eq!(timeline.end_frame(), 4);
+            assert_eq!(timeline.distance_for(4, 2.5), 2.0);
+        }
+    }
+
+    #[test]
+    fn single_frame_animation() {
+        let mut timeline = timeline(PlaybackMode::Bounce);
+        timeline.totalframe = 1;
+
+        assert_eq!(timeline.bounds(), (0, 0));
+        assert_eq!(frames(&timeline, 0..3), [0, 0, 0]);
+    }
+
+    #[test]
+    fn ended_frame_per_mode() {
+        let ended = |playback_mode, reversed| {
+            let mut timeline = timeline(playback_mode);
+            timeline.reversed = reversed;
+            timeline.end_frame()
+        };
+
+        assert_eq!(ended(PlaybackMode::Forward, false), 9);
+        assert_eq!(ended(PlaybackMode::Reverse, false), 0);
+        assert_eq!(ended(PlaybackMode::Bounce, false), 0);
+        assert_eq!(ended(PlaybackMode::ReverseBounce, false), 9);
+
+        assert_eq!(ended(PlaybackMode::Forward, true), 0);
+        assert_eq!(ended(PlaybackMode::Bounce, true), 9);
+    }
+
+    #[test]
+    fn ended_frame_closes_an_iteration() {
+        for playback_mode in [
+            PlaybackMode::Forward,
+            PlaybackMode::Reverse,
+            PlaybackMode::Bounce,
+            PlaybackMode::ReverseBounce,
+        ] {
+            let timeline = timeline(playback_mode);
+            let period = timeline.period();
+
+            assert_eq!(timeline.iteration(period - 1.0), 0);
+            assert_eq!(timeline.iteration(period), 1);
+
+            // bouncing ends where it started, the rest on the last frame they went through
+            if timeline.is_bounce() {
+                assert_eq!(timeline.frame_at(period), timeline.end_frame());
+                assert_eq!(timeline.end_frame(), timeline.start_frame());
+            } else {
+                assert_eq!(timeline.frame_at(period - 1.0), timeline.end_frame());
+            }
+        }
+    }
+
+    #[test]
+    fn distance_for_keeps_iteration_and_leg() {
+        let timeline = timeline(PlaybackMode::Bounce);
+
+        // going up in the second iteration
+        assert_eq!(timeline.distance_for(4, 20.0), 22.0);
+        // coming back down in the second iteration
+        assert_eq!(timeline.distance_for(4, 30.0), 32.0);
+
+        for near in [0.0, 5.0, 12.0, 20.0, 30.0] {
+            let distance = timeline.distance_for(6, near);
+            assert_eq!(timeline.frame_at(distance), 6);
+            assert_eq!(timeline.iteration(distance), timeline.iteration(near));
+        }
+
+        // frames outside the bounds are clamped
+        let mut timeline = timeline;
+        timeline.segment = Some((3, 6));
+        assert_eq!(timeline.frame_at(timeline.distance_for(8, 0.0)), 5);
+        assert_eq!(timeline.frame_at(timeline.distance_for(0, 0.0)), 3);
+    }
+
+    #[test]
+    fn same_frames() {
+        let timeline = timeline(PlaybackMode::Forward);
+
+        let mut faster = timeline;
+        faster.speed = 3.0;
+        assert!(timeline.same_frames(&faster));
+
+        let mut flipped = timeline;
+        flipped.reversed = true;
+        assert!(!timeline.same_frames(&flipped));
+
+        let mut segment = timeline;
+        segment.segment = Some((0, 5));
+        assert!(!timeline.same_frames(&segment));
+    }
+}